@@ -0,0 +1,65 @@
+#![feature(test)]
+
+extern crate glutin;
+extern crate glium;
+extern crate test;
+
+use test::Bencher;
+
+#[path = "../tests/support/mod.rs"]
+mod support;
+
+fn build_program(display: &glium::Display) -> glium::Program {
+    glium::Program::from_source(display,
+        "
+            #version 110
+
+            uniform mat4 matrix;
+
+            attribute vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0) * matrix;
+            }
+        ",
+        "
+            #version 110
+
+            uniform vec4 color;
+
+            void main() {
+                gl_FragColor = color;
+            }
+        ",
+        None).unwrap()
+}
+
+#[bench]
+fn get_uniform_existing(b: &mut Bencher) {
+    let display = support::build_display();
+    let program = build_program(&display);
+
+    b.iter(|| {
+        test::black_box(program.get_uniform("matrix"));
+    });
+}
+
+#[bench]
+fn get_uniform_missing(b: &mut Bencher) {
+    let display = support::build_display();
+    let program = build_program(&display);
+
+    b.iter(|| {
+        test::black_box(program.get_uniform("maybe_missing"));
+    });
+}
+
+#[bench]
+fn get_frag_data_location_missing(b: &mut Bencher) {
+    let display = support::build_display();
+    let program = build_program(&display);
+
+    b.iter(|| {
+        test::black_box(program.get_frag_data_location("maybe_missing"));
+    });
+}
@@ -37,7 +37,9 @@ fn generate_gl_bindings<W>(dest: &mut W) where W: Write {
                 "GL_ARB_depth_texture".to_string(),
                 "GL_ARB_direct_state_access".to_string(),
                 "GL_ARB_geometry_shader4".to_string(),
+                "GL_ARB_gl_spirv".to_string(),
                 "GL_ARB_invalidate_subdata".to_string(),
+                "GL_ARB_separate_shader_objects".to_string(),
                 "GL_ARB_shader_objects".to_string(),
                 "GL_ARB_texture_float".to_string(),
                 "GL_ARB_texture_multisample".to_string(),
@@ -56,8 +58,9 @@ fn generate_gl_bindings<W>(dest: &mut W) where W: Write {
                 "GL_EXT_transform_feedback".to_string(),
                 "GL_KHR_robustness".to_string(),
                 "GL_NVX_gpu_memory_info".to_string(),
+                "GL_NV_mesh_shader".to_string(),
             ],
-            version: "4.5".to_string(),
+            version: "4.6".to_string(),
             profile: "compatibility".to_string(),
         };
 
@@ -73,6 +76,7 @@ fn generate_gl_bindings<W>(dest: &mut W) where W: Write {
             api: gl_generator::registry::Ns::Gles2.to_string(),
             extensions: vec![
                 "GL_EXT_disjoint_timer_query".to_string(),
+                "GL_EXT_separate_shader_objects".to_string(),
                 "GL_KHR_debug".to_string(),
                 "GL_OES_texture_npot".to_string(),
                 "GL_OES_vertex_array_object".to_string(),
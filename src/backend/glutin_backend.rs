@@ -151,6 +151,37 @@ impl GlutinFacade {
         self.context.get_max_viewport_dimensions()
     }
 
+    /// Returns the list of program binary formats supported by the backend.
+    pub fn get_program_binary_formats(&self) -> Vec<u32> {
+        self.context.get_program_binary_formats()
+    }
+
+    /// Returns the list of vendor-specific shader binary formats supported by the backend.
+    pub fn get_shader_binary_formats(&self) -> Vec<u32> {
+        self.context.get_shader_binary_formats()
+    }
+
+    /// Returns the handles of all the programs that currently exist on this display.
+    ///
+    /// The programs themselves are owned by the user, so only their raw handles are tracked
+    /// instead of weak references to them. A handle is removed from the list when its program
+    /// is destroyed. Compare them with the `Handle` returned by `GlObject::get_id` on a
+    /// `Program`.
+    ///
+    /// Only available with the `debug_live_programs` feature, which otherwise adds no overhead.
+    #[cfg(feature = "debug_live_programs")]
+    pub fn live_programs(&self) -> Vec<::Handle> {
+        self.context.live_programs()
+    }
+
+    /// Sets whether errors reported by the debug output should be taken into account while
+    /// linking programs.
+    ///
+    /// See `Context::set_report_debug_output_during_linking`.
+    pub fn set_report_debug_output_during_linking(&self, value: bool) {
+        self.context.set_report_debug_output_during_linking(value)
+    }
+
     /// Releases the shader compiler, indicating that no new programs will be created for a while.
     ///
     /// # Features
@@ -1,4 +1,5 @@
 use context::ExtensionsList;
+use context::extensions::get_extensions_strings;
 use version::Version;
 use version::Api;
 use gl;
@@ -35,6 +36,25 @@ pub struct Capabilities {
 
     /// Maximum number of vertices per patch. `None` if tessellation is not supported.
     pub max_patch_vertices: Option<gl::types::GLint>,
+
+    /// Maximum number of vertex attribute slots that a program can use.
+    pub max_vertex_attribs: gl::types::GLint,
+
+    /// Maximum number of vertices that a geometry shader can output. `None` if geometry
+    /// shaders are not supported.
+    pub max_geometry_output_vertices: Option<gl::types::GLint>,
+
+    /// List of formats that are accepted by `glProgramBinary`. Empty if program binaries are
+    /// not supported.
+    pub program_binary_formats: Vec<gl::types::GLenum>,
+
+    /// List of formats that are accepted by `glShaderBinary`, except SPIR-V. Empty if shader
+    /// binaries are not supported.
+    pub shader_binary_formats: Vec<gl::types::GLenum>,
+
+    /// Names of all the extensions supported by the context, including the ones that glium
+    /// doesn't use.
+    pub extensions: Vec<String>,
 }
 
 /// Loads the capabilities.
@@ -145,5 +165,67 @@ pub fn get_capabilities(gl: &gl::Gl, version: &Version, extensions: &ExtensionsL
         } else {
             None
         },
+
+        max_vertex_attribs: unsafe {
+            let mut val = 8;
+            gl.GetIntegerv(gl::MAX_VERTEX_ATTRIBS, &mut val);
+            val
+        },
+
+        max_geometry_output_vertices: if version >= &Version(Api::Gl, 3, 2) ||
+            extensions.gl_arb_geometry_shader4 || extensions.gl_ext_geometry_shader4
+        {
+            Some(unsafe {
+                let mut val = 0;
+                gl.GetIntegerv(gl::MAX_GEOMETRY_OUTPUT_VERTICES, &mut val);
+                val
+            })
+
+        } else {
+            None
+        },
+
+        program_binary_formats: if version >= &Version(Api::Gl, 4, 1) ||
+            version >= &Version(Api::GlEs, 3, 0) || extensions.gl_arb_get_programy_binary
+        {
+            unsafe {
+                let mut num = 0;
+                gl.GetIntegerv(gl::NUM_PROGRAM_BINARY_FORMATS, &mut num);
+
+                let mut formats: Vec<gl::types::GLint> = Vec::with_capacity(num as usize);
+                if num >= 1 {
+                    gl.GetIntegerv(gl::PROGRAM_BINARY_FORMATS, formats.as_mut_ptr());
+                    formats.set_len(num as usize);
+                }
+
+                formats.into_iter().map(|f| f as gl::types::GLenum).collect()
+            }
+
+        } else {
+            Vec::new()
+        },
+
+        shader_binary_formats: if version >= &Version(Api::Gl, 4, 1) ||
+            version >= &Version(Api::GlEs, 2, 0)
+        {
+            unsafe {
+                let mut num = 0;
+                gl.GetIntegerv(gl::NUM_SHADER_BINARY_FORMATS, &mut num);
+
+                let mut formats: Vec<gl::types::GLint> = Vec::with_capacity(num as usize);
+                if num >= 1 {
+                    gl.GetIntegerv(gl::SHADER_BINARY_FORMATS, formats.as_mut_ptr());
+                    formats.set_len(num as usize);
+                }
+
+                formats.into_iter().map(|f| f as gl::types::GLenum)
+                       .filter(|&f| f != gl::SHADER_BINARY_FORMAT_SPIR_V_ARB).collect()
+            }
+
+        } else {
+            Vec::new()
+        },
+
+        extensions: get_extensions_strings(gl),
     }
 }
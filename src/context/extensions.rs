@@ -16,20 +16,28 @@ pub struct ExtensionsList {
     pub gl_arb_depth_texture: bool,
     /// GL_ARB_direct_state_access
     pub gl_arb_direct_state_access: bool,
+    /// GL_ARB_enhanced_layouts
+    pub gl_arb_enhanced_layouts: bool,
     /// GL_ARB_fragment_shader
     pub gl_arb_fragment_shader: bool,
     /// GL_ARB_geometry_shader4
     pub gl_arb_geometry_shader4: bool,
     /// GL_ARB_get_program_binary
     pub gl_arb_get_programy_binary: bool,
+    /// GL_ARB_gl_spirv
+    pub gl_arb_gl_spirv: bool,
     /// GL_ARB_instanced_arrays
     pub gl_arb_instanced_arrays: bool,
     /// GL_ARB_invalidate_subdata
     pub gl_arb_invalidate_subdata: bool,
     /// GL_ARB_map_buffer_range
     pub gl_arb_map_buffer_range: bool,
+    /// GL_ARB_program_interface_query
+    pub gl_arb_program_interface_query: bool,
     /// GL_ARB_sampler_objects
     pub gl_arb_sampler_objects: bool,
+    /// GL_ARB_separate_shader_objects
+    pub gl_arb_separate_shader_objects: bool,
     /// GL_ARB_shader_objects
     pub gl_arb_shader_objects: bool,
     /// GL_ARB_sync
@@ -72,6 +80,8 @@ pub struct ExtensionsList {
     pub gl_ext_gpu_shader4: bool,
     /// GL_EXT_packed_depth_stencil
     pub gl_ext_packed_depth_stencil: bool,
+    /// GL_EXT_separate_shader_objects
+    pub gl_ext_separate_shader_objects: bool,
     /// GL_EXT_texture_filter_anisotropic
     pub gl_ext_texture_filter_anisotropic: bool,
     /// GL_EXT_texture_integer
@@ -82,6 +92,8 @@ pub struct ExtensionsList {
     pub gl_khr_debug: bool,
     /// GL_NVX_gpu_memory_info
     pub gl_nvx_gpu_memory_info: bool,
+    /// GL_NV_mesh_shader
+    pub gl_nv_mesh_shader: bool,
     /// GL_OES_vertex_array_object
     pub gl_oes_vertex_array_object: bool,
 }
@@ -96,13 +108,17 @@ pub fn get_extensions(gl: &gl::Gl) -> ExtensionsList {
         gl_arb_debug_output: false,
         gl_arb_depth_texture: false,
         gl_arb_direct_state_access: false,
+        gl_arb_enhanced_layouts: false,
         gl_arb_fragment_shader: false,
         gl_arb_geometry_shader4: false,
         gl_arb_get_programy_binary: false,
+        gl_arb_gl_spirv: false,
         gl_arb_instanced_arrays: false,
         gl_arb_invalidate_subdata: false,
         gl_arb_map_buffer_range: false,
+        gl_arb_program_interface_query: false,
         gl_arb_sampler_objects: false,
+        gl_arb_separate_shader_objects: false,
         gl_arb_shader_objects: false,
         gl_arb_sync: false,
         gl_arb_tessellation_shader: false,
@@ -124,11 +140,13 @@ pub fn get_extensions(gl: &gl::Gl) -> ExtensionsList {
         gl_ext_geometry_shader4: false,
         gl_ext_gpu_shader4: false,
         gl_ext_packed_depth_stencil: false,
+        gl_ext_separate_shader_objects: false,
         gl_ext_texture_filter_anisotropic: false,
         gl_ext_texture_integer: false,
         gl_ext_transform_feedback: false,
         gl_khr_debug: false,
         gl_nvx_gpu_memory_info: false,
+        gl_nv_mesh_shader: false,
         gl_oes_vertex_array_object: false,
     };
 
@@ -140,13 +158,17 @@ pub fn get_extensions(gl: &gl::Gl) -> ExtensionsList {
             "GL_ARB_debug_output" => extensions.gl_arb_debug_output = true,
             "GL_ARB_depth_texture" => extensions.gl_arb_depth_texture = true,
             "GL_ARB_direct_state_access" => extensions.gl_arb_direct_state_access = true,
+            "GL_ARB_enhanced_layouts" => extensions.gl_arb_enhanced_layouts = true,
             "GL_ARB_fragment_shader" => extensions.gl_arb_fragment_shader = true,
             "GL_ARB_geometry_shader4" => extensions.gl_arb_geometry_shader4 = true,
             "GL_ARB_get_program_binary" => extensions.gl_arb_get_programy_binary = true,
+            "GL_ARB_gl_spirv" => extensions.gl_arb_gl_spirv = true,
             "GL_ARB_instanced_arrays" => extensions.gl_arb_instanced_arrays = true,
             "GL_ARB_invalidate_subdata" => extensions.gl_arb_invalidate_subdata = true,
             "GL_ARB_map_buffer_range" => extensions.gl_arb_map_buffer_range = true,
+            "GL_ARB_program_interface_query" => extensions.gl_arb_program_interface_query = true,
             "GL_ARB_sampler_objects" => extensions.gl_arb_sampler_objects = true,
+            "GL_ARB_separate_shader_objects" => extensions.gl_arb_separate_shader_objects = true,
             "GL_ARB_shader_objects" => extensions.gl_arb_shader_objects = true,
             "GL_ARB_sync" => extensions.gl_arb_sync = true,
             "GL_ARB_tessellation_shader" => extensions.gl_arb_tessellation_shader = true,
@@ -168,11 +190,13 @@ pub fn get_extensions(gl: &gl::Gl) -> ExtensionsList {
             "GL_EXT_geometry_shader4" => extensions.gl_ext_geometry_shader4 = true,
             "GL_EXT_gpu_shader4" => extensions.gl_ext_gpu_shader4 = true,
             "GL_EXT_packed_depth_stencil" => extensions.gl_ext_packed_depth_stencil = true,
+            "GL_EXT_separate_shader_objects" => extensions.gl_ext_separate_shader_objects = true,
             "GL_EXT_texture_filter_anisotropic" => extensions.gl_ext_texture_filter_anisotropic = true,
             "GL_EXT_texture_integer" => extensions.gl_ext_texture_integer = true,
             "GL_EXT_transform_feedback" => extensions.gl_ext_transform_feedback = true,
             "GL_KHR_debug" => extensions.gl_khr_debug = true,
             "GL_NVX_gpu_memory_info" => extensions.gl_nvx_gpu_memory_info = true,
+            "GL_NV_mesh_shader" => extensions.gl_nv_mesh_shader = true,
             "GL_OES_vertex_array_object" => extensions.gl_oes_vertex_array_object = true,
             _ => ()
         }
@@ -181,7 +205,8 @@ pub fn get_extensions(gl: &gl::Gl) -> ExtensionsList {
     extensions
 }
 
-fn get_extensions_strings(gl: &gl::Gl) -> Vec<String> {
+/// Returns the names of all the extensions supported by the context.
+pub fn get_extensions_strings(gl: &gl::Gl) -> Vec<String> {
     unsafe {
         let list = gl.GetString(gl::EXTENSIONS);
 
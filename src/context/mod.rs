@@ -13,6 +13,8 @@ use std::rc::Rc;
 
 use GliumCreationError;
 use ContextExt;
+#[cfg(feature = "debug_live_programs")]
+use Handle;
 use backend::Backend;
 use version;
 use version::Api;
@@ -46,8 +48,14 @@ pub struct Context {
     backend: RefCell<Box<Backend>>,
     check_current_context: bool,
 
+    // if true, the backend's context is known to be current and `make_current` doesn't check it
+    assume_current: Cell<bool>,
+
     report_debug_output_errors: Cell<bool>,
 
+    // if true, debug output errors are not ignored while linking programs
+    report_debug_output_during_linking: Cell<bool>,
+
     // we maintain a list of FBOs
     // the option is here to destroy the container
     pub framebuffer_objects: Option<fbo::FramebuffersContainer>,
@@ -57,6 +65,23 @@ pub struct Context {
     // we maintain a list of samplers for each possible behavior
     pub samplers: RefCell<HashMap<uniforms::SamplerBehavior, sampler_object::SamplerObject, 
                           DefaultState<util::FnvHasher>>>,
+
+    // list of the programs that are alive, zero-sized without the `debug_live_programs` feature
+    pub live_programs: LivePrograms,
+}
+
+#[cfg(feature = "debug_live_programs")]
+pub type LivePrograms = RefCell<Vec<Handle>>;
+#[cfg(not(feature = "debug_live_programs"))]
+pub type LivePrograms = ();
+
+#[cfg(feature = "debug_live_programs")]
+fn new_live_programs() -> LivePrograms {
+    RefCell::new(Vec::new())
+}
+
+#[cfg(not(feature = "debug_live_programs"))]
+fn new_live_programs() -> LivePrograms {
 }
 
 pub struct CommandContext<'a, 'b> {
@@ -110,11 +135,14 @@ impl Context {
             extensions: extensions,
             capabilities: capabilities,
             report_debug_output_errors: report_debug_output_errors,
+            report_debug_output_during_linking: Cell::new(false),
             backend: RefCell::new(Box::new(backend)),
             check_current_context: check_current_context,
+            assume_current: Cell::new(false),
             framebuffer_objects: Some(fbo::FramebuffersContainer::new()),
             vertex_array_objects: vertex_array_object::VertexAttributesSystem::new(),
             samplers: RefCell::new(HashMap::with_hash_state(Default::default())),
+            live_programs: new_live_programs(),
         });
 
         init_debug_callback(&context);
@@ -205,6 +233,44 @@ impl Context {
         (d.0 as u32, d.1 as u32)
     }
 
+    /// Returns the list of program binary formats supported by the backend.
+    ///
+    /// A `Binary` whose format is not in this list can't be loaded. The list is empty if the
+    /// backend doesn't support loading program binaries.
+    pub fn get_program_binary_formats(&self) -> Vec<u32> {
+        self.capabilities().program_binary_formats.clone()
+    }
+
+    /// Returns the handles of all the programs that currently exist on this context.
+    ///
+    /// This is meant for debugging tools, and is only available with the `debug_live_programs`
+    /// feature. Programs are owned by the user, so only their handles are tracked. You can
+    /// compare them with the `Handle` returned by `GlObject::get_id` on a `Program`.
+    #[cfg(feature = "debug_live_programs")]
+    pub fn live_programs(&self) -> Vec<Handle> {
+        self.live_programs.borrow().clone()
+    }
+
+    /// Returns the list of vendor-specific shader binary formats supported by the backend.
+    ///
+    /// These are the formats that can be used with `ProgramCreationInput::ShaderBinaries`.
+    /// The list is empty if the backend doesn't support loading shader binaries.
+    pub fn get_shader_binary_formats(&self) -> Vec<u32> {
+        self.capabilities().shader_binary_formats.clone()
+    }
+
+    /// Sets whether errors reported by the debug output should be taken into account while
+    /// linking programs.
+    ///
+    /// By default, glium ignores the debug output while linking, as linking errors are
+    /// already returned as `ProgramCreationError`s. However some drivers give more details
+    /// through the debug output than in the info log. If you enable this, the debug output
+    /// errors emitted while linking will panic with the driver's message, like any other
+    /// debug output error.
+    pub fn set_report_debug_output_during_linking(&self, value: bool) {
+        self.report_debug_output_during_linking.set(value);
+    }
+
     /// Releases the shader compiler, indicating that no new programs will be created for a while.
     ///
     /// This method is a no-op if it's not available in the implementation.
@@ -312,8 +378,25 @@ impl ContextExt for Context {
         self.report_debug_output_errors.set(value);
     }
 
+    fn get_report_debug_output_during_linking(&self) -> bool {
+        self.report_debug_output_during_linking.get()
+    }
+
+    fn set_assume_current(&self, value: bool) -> bool {
+        if value {
+            let backend = self.backend.borrow();
+            if !backend.is_current() {
+                unsafe { backend.make_current() };
+            }
+        }
+
+        let previous = self.assume_current.get();
+        self.assume_current.set(value);
+        previous
+    }
+
     fn make_current<'a>(&'a self) -> CommandContext<'a, 'a> {
-        if self.check_current_context {
+        if self.check_current_context && !self.assume_current.get() {
             let backend = self.backend.borrow();
             if !backend.is_current() {
                 unsafe { backend.make_current() };
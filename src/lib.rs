@@ -158,6 +158,8 @@ target.finish();
 #![feature(core, std_misc, collections)]     // TODO: remove after 1.0 beta
 
 #![feature(unsafe_destructor)]
+#![cfg_attr(feature = "serde_serialization", feature(custom_derive, plugin))]
+#![cfg_attr(feature = "serde_serialization", plugin(serde_macros))]
 #![unstable]
 #![warn(missing_docs)]
 
@@ -173,6 +175,8 @@ extern crate image;
 extern crate libc;
 #[cfg(feature = "nalgebra")]
 extern crate nalgebra;
+#[cfg(feature = "serde_serialization")]
+extern crate serde;
 
 pub use draw_parameters::{BlendingFunction, LinearBlendingFactor, BackfaceCullingMode};
 pub use draw_parameters::{DepthTest, PolygonMode, DrawParameters};
@@ -262,6 +266,13 @@ trait ContextExt {
     /// Sets whether the context's debug output callback should take errors into account.
     fn set_report_debug_output_errors(&self, value: bool);
 
+    /// Returns true if debug output errors should be taken into account while linking.
+    fn get_report_debug_output_during_linking(&self) -> bool;
+
+    /// Sets whether `make_current` can assume that the backend's context is already current.
+    /// Makes the context current if `value` is true. Returns the previous value.
+    fn set_assume_current(&self, value: bool) -> bool;
+
     /// Start executing OpenGL commands by checking the current context.
     fn make_current<'a>(&'a self) -> context::CommandContext<'a, 'a>;
 }
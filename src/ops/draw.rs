@@ -188,7 +188,7 @@ pub fn draw<'a, I, U, V>(context: &Context, framebuffer: Option<&FramebufferAtta
                     }
                 };
 
-            } else if let Some(block) = program.get_uniform_blocks().get(name) {
+            } else if let Some(block) = program.get_uniform_block(name) {
                 let fence = match bind_uniform_block(&mut ctxt, value, block,
                                                      program.get_id(),
                                                     &mut active_buffer_binding, name)
@@ -0,0 +1,102 @@
+use backend::Facade;
+use context::Context;
+use ContextExt;
+
+use std::rc::Rc;
+
+use program::{IntoProgramCreationInput, Program, ProgramCreationError};
+
+/// Checks whether the context is current only once per program when creating multiple
+/// programs.
+///
+/// Normally each step of a program creation checks whether the context is current, and makes
+/// it current if it isn't. When a program is created with `new_program` or `from_source`, the
+/// check is done once at the start and skipped for the rest of the creation. This is useful
+/// when loading a large number of programs.
+///
+/// The check is only skipped while one of these two functions is running. Other operations on
+/// the context, even while this object is alive, are not affected.
+///
+/// This object implements `Facade`, but passing it to other functions doesn't skip any check.
+pub struct ProgramCreationGuard<'a> {
+    context: &'a Rc<Context>,
+}
+
+impl<'a> ProgramCreationGuard<'a> {
+    /// Builds the guard.
+    pub fn new<F>(facade: &'a F) -> ProgramCreationGuard<'a> where F: Facade {
+        ProgramCreationGuard {
+            context: facade.get_context(),
+        }
+    }
+
+    /// Builds a new program. Same as `Program::new`.
+    pub fn new_program<'b, I>(&self, input: I) -> Result<Program, ProgramCreationError>
+                              where I: IntoProgramCreationInput<'b>
+    {
+        let _scope = AssumeCurrentScope::new(self.context);
+        Program::new(self, input)
+    }
+
+    /// Builds a new program from GLSL source code. Same as `Program::from_source`.
+    pub fn from_source<'b>(&self, vertex_shader: &'b str, fragment_shader: &'b str,
+                           geometry_shader: Option<&'b str>)
+                           -> Result<Program, ProgramCreationError>
+    {
+        let _scope = AssumeCurrentScope::new(self.context);
+        Program::from_source(self, vertex_shader, fragment_shader, geometry_shader)
+    }
+}
+
+impl<'a> Facade for ProgramCreationGuard<'a> {
+    fn get_context(&self) -> &Rc<Context> {
+        self.context
+    }
+}
+
+/// Makes the context current and skips the is-current check until it is destroyed.
+///
+/// Only lives for the duration of a single program creation, and is never handed to the user,
+/// so the check can't be left disabled.
+struct AssumeCurrentScope<'a> {
+    context: &'a Rc<Context>,
+    previous: bool,
+}
+
+impl<'a> AssumeCurrentScope<'a> {
+    fn new(context: &'a Rc<Context>) -> AssumeCurrentScope<'a> {
+        let previous = context.set_assume_current(true);
+
+        AssumeCurrentScope {
+            context: context,
+            previous: previous,
+        }
+    }
+}
+
+#[unsafe_destructor]
+impl<'a> Drop for AssumeCurrentScope<'a> {
+    fn drop(&mut self) {
+        self.context.set_assume_current(self.previous);
+    }
+}
+
+/// Calls the closure with a `ProgramCreationGuard`, so that the context is only checked once
+/// for each program that is created inside the closure.
+///
+/// # Example
+///
+/// ```no_run
+/// # let display: glium::Display = unsafe { std::mem::uninitialized() };
+/// # let vertex_source = ""; let fragment_source = "";
+/// let programs = glium::program::with_program_batch(&display, |batch| {
+///     (0 .. 16).map(|_| batch.from_source(vertex_source, fragment_source, None).unwrap())
+///              .collect::<Vec<_>>()
+/// });
+/// ```
+pub fn with_program_batch<F, C, R>(facade: &F, callback: C) -> R
+                                   where F: Facade, C: FnOnce(&ProgramCreationGuard) -> R
+{
+    let guard = ProgramCreationGuard::new(facade);
+    callback(&guard)
+}
@@ -0,0 +1,172 @@
+//! Splits a source file that contains multiple shader stages.
+//!
+//! The stages are delimited by `#pragma stage <name>` lines. Everything before the first
+//! pragma is shared and is included in every stage.
+
+/// The source code of each stage extracted from a combined source file.
+pub struct CombinedSource {
+    pub vertex_shader: String,
+    pub tessellation_control_shader: Option<String>,
+    pub tessellation_evaluation_shader: Option<String>,
+    pub geometry_shader: Option<String>,
+    pub fragment_shader: String,
+}
+
+/// Splits a combined source file into stages.
+///
+/// `#line` directives are inserted so that the line numbers reported by the compiler are the
+/// ones of the combined file. Returns an error message if a `#pragma stage` is invalid.
+pub fn split(source: &str) -> Result<CombinedSource, String> {
+    let lines = source.lines().collect::<Vec<_>>();
+
+    // the line of the `#version` directive, and its index
+    let mut version = None;
+
+    // for each stage, its name and the index of its first line
+    let mut stages: Vec<(&str, usize)> = Vec::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+
+        if trimmed.starts_with("#version") {
+            if version.is_none() {
+                version = Some((trimmed, index));
+            }
+            continue;
+        }
+
+        if !trimmed.starts_with("#pragma") {
+            continue;
+        }
+
+        let mut words = trimmed["#pragma".len() ..].split(|c: char| c.is_whitespace())
+                                                   .filter(|w| !w.is_empty());
+        if words.next() != Some("stage") {
+            continue;
+        }
+
+        let name = match (words.next(), words.next()) {
+            (Some(name), None) => name,
+            _ => return Err(format!("Line {}: `#pragma stage` must be followed by the name of \
+                                     exactly one stage", index + 1))
+        };
+
+        match name {
+            "vertex" | "tessellation_control" | "tessellation_evaluation" | "geometry" |
+            "fragment" => (),
+            _ => return Err(format!("Line {}: unknown stage `{}`, expected `vertex`, \
+                                     `tessellation_control`, `tessellation_evaluation`, \
+                                     `geometry` or `fragment`", index + 1, name))
+        };
+
+        if stages.iter().any(|&(n, _)| n == name) {
+            return Err(format!("Line {}: stage `{}` is defined multiple times", index + 1, name));
+        }
+
+        stages.push((name, index + 1));
+    }
+
+    // the shared section ends at the first pragma
+    let shared_end = stages.get(0).map(|&(_, start)| start - 1).unwrap_or(lines.len());
+    let old_line_semantics = version.map(|(v, _)| uses_old_line_semantics(v)).unwrap_or(true);
+
+    let build_stage = |start: usize, end: usize| -> String {
+        let mut result = String::new();
+
+        if let Some((version, _)) = version {
+            result.push_str(version);
+            result.push_str("\n");
+        }
+
+        result.push_str(&line_directive(1, old_line_semantics));
+        for index in (0 .. shared_end) {
+            push_line(&mut result, &lines, index, version);
+        }
+
+        result.push_str(&line_directive(start + 1, old_line_semantics));
+        for index in (start .. end) {
+            push_line(&mut result, &lines, index, version);
+        }
+
+        result
+    };
+
+    let mut vertex_shader = None;
+    let mut tessellation_control_shader = None;
+    let mut tessellation_evaluation_shader = None;
+    let mut geometry_shader = None;
+    let mut fragment_shader = None;
+
+    for (position, &(name, start)) in stages.iter().enumerate() {
+        let end = stages.get(position + 1).map(|&(_, next)| next - 1).unwrap_or(lines.len());
+        let stage = Some(build_stage(start, end));
+
+        match name {
+            "vertex" => vertex_shader = stage,
+            "tessellation_control" => tessellation_control_shader = stage,
+            "tessellation_evaluation" => tessellation_evaluation_shader = stage,
+            "geometry" => geometry_shader = stage,
+            "fragment" => fragment_shader = stage,
+            _ => unreachable!()
+        }
+    }
+
+    let vertex_shader = match vertex_shader {
+        Some(s) => s,
+        None => return Err(format!("Missing `#pragma stage vertex`"))
+    };
+
+    let fragment_shader = match fragment_shader {
+        Some(s) => s,
+        None => return Err(format!("Missing `#pragma stage fragment`"))
+    };
+
+    Ok(CombinedSource {
+        vertex_shader: vertex_shader,
+        tessellation_control_shader: tessellation_control_shader,
+        tessellation_evaluation_shader: tessellation_evaluation_shader,
+        geometry_shader: geometry_shader,
+        fragment_shader: fragment_shader,
+    })
+}
+
+/// Adds a line of the combined source to a stage.
+///
+/// The `#version` directive has already been written at the start of the stage, so it is
+/// replaced with an empty line in order to keep the line numbers.
+fn push_line(result: &mut String, lines: &[&str], index: usize,
+             version: Option<(&str, usize)>)
+{
+    if version.map(|(_, i)| i == index).unwrap_or(false) {
+        result.push_str("\n");
+    } else {
+        result.push_str(lines[index]);
+        result.push_str("\n");
+    }
+}
+
+/// Returns a `#line` directive so that the next line is reported as `line`.
+pub fn line_directive(line: usize, old_semantics: bool) -> String {
+    if old_semantics {
+        format!("#line {}\n", line - 1)
+    } else {
+        format!("#line {}\n", line)
+    }
+}
+
+/// Before GLSL 3.30 and GLSL ES 3.00, the line following `#line N` is numbered `N + 1`.
+pub fn uses_old_line_semantics(version: &str) -> bool {
+    let mut words = version["#version".len() ..].split(|c: char| c.is_whitespace())
+                                                .filter(|w| !w.is_empty());
+
+    let number: u32 = match words.next().and_then(|n| n.parse().ok()) {
+        Some(n) => n,
+        None => return true
+    };
+
+    if words.next() == Some("es") {
+        number < 300
+    } else {
+        number < 330
+    }
+}
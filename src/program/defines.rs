@@ -0,0 +1,84 @@
+//! Injects `#define`, `#version` and precision directives in GLSL source code.
+
+use program::combined::{line_directive, uses_old_line_semantics};
+use program::shader::strip_bom;
+
+/// Adds a `#define` directive for each `(name, value)` to a source code.
+///
+/// The directives are inserted after the `#version` directive, or at the start of the source
+/// if there is none. A `#line` directive is then added so that the line numbers reported by
+/// the compiler are still the ones of the original source.
+pub fn inject_defines(source: &str, defines: &[(&str, &str)]) -> String {
+    let lines = strip_bom(source).lines().collect::<Vec<_>>();
+    let version = lines.iter().position(|line| line.trim().starts_with("#version"));
+
+    let mut injected = String::new();
+    for &(name, value) in defines.iter() {
+        injected.push_str(&format!("#define {} {}\n", name, value));
+    }
+
+    insert_after_line(&lines, version, version, &injected)
+}
+
+/// Adds a default precision for floats to the source code of an OpenGL ES fragment shader.
+///
+/// The declaration is inserted after the `#version` directive and the `#extension`
+/// directives that follow it, as extensions must be enabled before any statement. `highp` is
+/// used if the implementation supports it in fragment shaders, and `mediump` otherwise.
+pub fn inject_default_precision(source: &str) -> String {
+    let lines = strip_bom(source).lines().collect::<Vec<_>>();
+    let version = lines.iter().position(|line| line.trim().starts_with("#version"));
+    let last_extension = lines.iter().rposition(|line| line.trim().starts_with("#extension"));
+
+    insert_after_line(&lines, last_extension.or(version), version,
+                      "#ifdef GL_FRAGMENT_PRECISION_HIGH\n\
+                       precision highp float;\n\
+                       #else\n\
+                       precision mediump float;\n\
+                       #endif\n")
+}
+
+/// Inserts `injected` after the line whose index is `after`, or at the start if it is `None`.
+///
+/// A `#line` directive is then added so that the line numbers reported by the compiler are
+/// still the ones of the original source. `version` is the index of the `#version` directive.
+fn insert_after_line(lines: &[&str], after: Option<usize>, version: Option<usize>,
+                     injected: &str) -> String
+{
+    let mut result = String::new();
+
+    // everything up to and including the line is kept as it is
+    let first_line = match after {
+        Some(index) => {
+            for line in lines[.. index + 1].iter() {
+                result.push_str(line);
+                result.push_str("\n");
+            }
+            index + 1
+        },
+        None => 0
+    };
+
+    result.push_str(injected);
+
+    let old_line_semantics = version.map(|index| uses_old_line_semantics(lines[index].trim()))
+                                    .unwrap_or(true);
+    result.push_str(&line_directive(first_line + 1, old_line_semantics));
+
+    for line in lines[first_line ..].iter() {
+        result.push_str(line);
+        result.push_str("\n");
+    }
+
+    result
+}
+
+/// Adds a `#version` directive at the start of a source code that doesn't have one.
+///
+/// A `#line` directive is added after it so that the line numbers reported by the compiler
+/// are still the ones of the original source.
+pub fn inject_version(source: &str, version: &str) -> String {
+    let directive = format!("#version {}", version);
+    let line = line_directive(1, uses_old_line_semantics(&directive));
+    format!("{}\n{}{}", directive, line, strip_bom(source))
+}
@@ -1,9 +1,24 @@
+use gl;
+use std::mem;
 use std::sync::{StaticMutex, MUTEX_INIT};
 
-pub use self::program::{Program, ProgramCreationError};
-pub use self::reflection::{Uniform, UniformBlock, UniformBlockMember};
-pub use self::reflection::{Attribute, TransformFeedbackVarying, TransformFeedbackMode};
+use ToGlEnum;
+use backend::Facade;
 
+pub use self::batch::{ProgramCreationGuard, with_program_batch};
+pub use self::program::{Program, ProgramCreationError, UniformBlocksIter, FragmentOutputsIter};
+pub use self::program::{ContextInfo, ImageUniformsIter, ShaderError, UniformsMatchingIter};
+pub use self::program::{AttribBinding, BindingError, ProgramFeature, AttributeMismatch};
+pub use self::program::{SamplersIter, ValidationError, UniformTypeMismatch};
+pub use self::program::ProgramReflectionVisitor;
+pub use self::reflection::{Uniform, UniformBlock, UniformBlockMember, ImageUniform, SamplerKind};
+pub use self::reflection::{Attribute, Output, TransformFeedbackVarying, TransformFeedbackMode};
+pub use self::reflection::StageMask;
+pub use self::shader::Shader;
+
+mod batch;
+mod combined;
+mod defines;
 mod program;
 mod reflection;
 mod shader;
@@ -29,6 +44,9 @@ pub enum ProgramCreationInput<'a> {
         geometry_shader: Option<&'a str>,
 
         /// Source code of the fragment shader.
+        ///
+        /// On OpenGL ES, a default precision for floats is added if the source code doesn't
+        /// declare one.
         fragment_shader: &'a str,
 
         /// The list of variables and mode to use for transform feedback.
@@ -36,12 +54,208 @@ pub enum ProgramCreationInput<'a> {
         /// The information specified here will be passed to the OpenGL linker. If you pass
         /// `None`, then you won't be able to use transform feedback.
         transform_feedback_varyings: Option<(Vec<String>, TransformFeedbackMode)>,
+
+        /// The color number and index to assign to outputs of the fragment shader before
+        /// linking, as `(name, color, index)`.
+        ///
+        /// The index selects the source of dual-source blending. Requires OpenGL 3.3, otherwise
+        /// program creation will return `DualSourceBlendingNotSupported`.
+        indexed_frag_data_locations: Option<&'a [(&'a str, u32, u32)]>,
+
+        /// Function called with the source code of each stage right before it is compiled,
+        /// and whose return value is compiled instead.
+        ///
+        /// This can be used to rewrite the source code in any way, for example to add debug
+        /// instrumentation. It is called last, after `#define` directives have been injected by
+        /// `recompile_with_defines`. Keeping `#line` directives correct is your responsibility.
+        ///
+        /// The transform can fail, for example if it resolves `#include` directives and a
+        /// file can't be found. Its error is then returned as a `PreprocessingError`.
+        ///
+        /// The transform is not retained by the program, which means that
+        /// `recompile_with_defines` doesn't apply it again.
+        source_transform: Option<&'a Fn(ShaderStage, &str) -> Result<String, String>>,
+
+        /// If `true`, the info log of the linker is kept in the program and can be retrieved
+        /// with `Program::link_log`.
+        retain_link_log: bool,
+
+        /// If `true`, a copy of the source code is kept in the program, which is required by
+        /// `Program::recompile_with_defines`.
+        retain_source: bool,
+
+        /// If `true`, program creation returns a `LinkingError` if one of the stages doesn't
+        /// seem to have any effect, for example a geometry shader that never emits a vertex.
+        ///
+        /// This is a best-effort check based on the source code, meant to catch mistakes early.
+        /// It can't detect every inert stage.
+        strict: bool,
+
+        /// What to do if the source code of a stage doesn't have a `#version` directive.
+        missing_version: MissingVersion<'a>,
+
+        /// If `true`, built-in uniforms and attributes (whose name starts with `gl_`) are
+        /// kept in the reflection data of the program.
+        ///
+        /// You usually want to pass `false`.
+        reflect_builtins: bool,
     },
 
     /// Use a precompiled binary.
     Binary {
         /// The data.
         data: Binary,
+
+        /// If `true`, built-in uniforms and attributes (whose name starts with `gl_`) are
+        /// kept in the reflection data of the program.
+        ///
+        /// You usually want to pass `false`.
+        reflect_builtins: bool,
+    },
+
+    /// Use a list of precompiled binaries.
+    ///
+    /// The binaries are tried in order, and the first one that can be loaded is used. This is
+    /// useful if you cached binaries produced by multiple drivers. If none of them can be
+    /// loaded, program creation returns a `LinkingError` containing the error of each binary.
+    Binaries {
+        /// The candidates.
+        data: &'a [Binary],
+
+        /// If `true`, built-in uniforms and attributes (whose name starts with `gl_`) are
+        /// kept in the reflection data of the program.
+        ///
+        /// You usually want to pass `false`.
+        reflect_builtins: bool,
+    },
+
+    /// Use SPIR-V modules.
+    ///
+    /// Requires OpenGL 4.6 or the `GL_ARB_gl_spirv` extension. Otherwise program creation
+    /// will return `CompilationNotSupported`.
+    SpirV {
+        /// List of stages of the program. Each stage is made of the SPIR-V module and the name
+        /// of its entry point.
+        ///
+        /// You must provide at least a vertex and a fragment shader.
+        stages: Vec<(ShaderStage, &'a [u8], &'a str)>,
+
+        /// Values to use for the specialization constants of the modules, indexed by
+        /// constant ID.
+        ///
+        /// The same list is passed when specializing each stage. Constants that are not in
+        /// this list keep the default value written in the module.
+        specialization_constants: &'a [(u32, SpecConstantValue)],
+
+        /// If `true`, the info log of the linker is kept in the program and can be retrieved
+        /// with `Program::link_log`.
+        retain_link_log: bool,
+
+        /// If `true`, built-in uniforms and attributes (whose name starts with `gl_`) are
+        /// kept in the reflection data of the program.
+        ///
+        /// You usually want to pass `false`.
+        reflect_builtins: bool,
+    },
+
+    /// Use vendor-specific binaries, one for each stage.
+    ///
+    /// This is mostly useful with OpenGL ES drivers that don't have a shader compiler. Each
+    /// stage is made of a format and of the binary data. The format must be one of the values
+    /// returned by `GL_SHADER_BINARY_FORMATS`, otherwise program creation will return
+    /// `CompilationNotSupported`.
+    ///
+    /// Use `SpirV` for SPIR-V modules.
+    ShaderBinaries {
+        /// List of stages of the program.
+        ///
+        /// You must provide at least a vertex and a fragment shader.
+        stages: Vec<(ShaderStage, u32, &'a [u8])>,
+
+        /// If `true`, the info log of the linker is kept in the program and can be retrieved
+        /// with `Program::link_log`.
+        retain_link_log: bool,
+
+        /// If `true`, built-in uniforms and attributes (whose name starts with `gl_`) are
+        /// kept in the reflection data of the program.
+        ///
+        /// You usually want to pass `false`.
+        reflect_builtins: bool,
+    },
+}
+
+/// What to do when the source code of a shader doesn't have a `#version` directive.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MissingVersion<'a> {
+    /// Compile the source code as it is. Desktop drivers then use GLSL 1.10.
+    Ignore,
+    /// Return a `CompilationError` that tells which stage is missing the directive.
+    Error,
+    /// Add `#version` followed by this value, for example `"330 core"`, at the start of the
+    /// source code.
+    Default(&'a str),
+}
+
+/// Value of a SPIR-V specialization constant.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SpecConstantValue {
+    /// A 32bits unsigned integer.
+    U32(u32),
+    /// A 32bits signed integer.
+    I32(i32),
+    /// A 32bits float.
+    F32(f32),
+    /// A boolean.
+    Bool(bool),
+}
+
+impl SpecConstantValue {
+    /// Returns the raw 32bits value to pass to `glSpecializeShader`.
+    fn to_raw(&self) -> gl::types::GLuint {
+        match *self {
+            SpecConstantValue::U32(v) => v,
+            SpecConstantValue::I32(v) => v as gl::types::GLuint,
+            SpecConstantValue::F32(v) => unsafe { mem::transmute(v) },
+            SpecConstantValue::Bool(v) => if v { 1 } else { 0 },
+        }
+    }
+}
+
+/// Stage of the pipeline that a shader is executed in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ShaderStage {
+    /// Vertex shader.
+    Vertex,
+    /// Tessellation control shader.
+    TessellationControl,
+    /// Tessellation evaluation shader.
+    TessellationEvaluation,
+    /// Geometry shader.
+    Geometry,
+    /// Fragment shader.
+    Fragment,
+    /// Mesh shader.
+    ///
+    /// Requires the `GL_NV_mesh_shader` extension. Programs that use mesh shaders have no
+    /// vertex attributes.
+    Mesh,
+    /// Task shader.
+    ///
+    /// Requires the `GL_NV_mesh_shader` extension.
+    Task,
+}
+
+impl ToGlEnum for ShaderStage {
+    fn to_glenum(&self) -> gl::types::GLenum {
+        match *self {
+            ShaderStage::Vertex => gl::VERTEX_SHADER,
+            ShaderStage::TessellationControl => gl::TESS_CONTROL_SHADER,
+            ShaderStage::TessellationEvaluation => gl::TESS_EVALUATION_SHADER,
+            ShaderStage::Geometry => gl::GEOMETRY_SHADER,
+            ShaderStage::Fragment => gl::FRAGMENT_SHADER,
+            ShaderStage::Mesh => gl::MESH_SHADER_NV,
+            ShaderStage::Task => gl::TASK_SHADER_NV,
+        }
     }
 }
 
@@ -87,11 +301,23 @@ impl<'a> IntoProgramCreationInput<'a> for SourceCode<'a> {
             geometry_shader: geometry_shader,
             fragment_shader: fragment_shader,
             transform_feedback_varyings: None,
+            indexed_frag_data_locations: None,
+            source_transform: None,
+            retain_link_log: false,
+            retain_source: false,
+            strict: false,
+            missing_version: MissingVersion::Ignore,
+            reflect_builtins: false,
         }
     }
 }
 
 /// Represents the compiled binary data of a program.
+///
+/// If the `serde_serialization` feature is enabled, this struct can be serialized and deserialized, which
+/// allows caching compiled programs on disk. The format is specific to the driver, so you
+/// should call `is_format_supported` on a deserialized binary before using it.
+#[cfg_attr(feature = "serde_serialization", derive(Serialize, Deserialize))]
 pub struct Binary {
     /// An implementation-defined format.
     pub format: u32,
@@ -100,10 +326,20 @@ pub struct Binary {
     pub content: Vec<u8>,
 }
 
+impl Binary {
+    /// Returns true if the format of this binary is supported by the backend.
+    ///
+    /// A binary whose format is not supported can't be loaded.
+    pub fn is_format_supported<F>(&self, facade: &F) -> bool where F: Facade {
+        facade.get_context().capabilities().program_binary_formats.contains(&self.format)
+    }
+}
+
 impl IntoProgramCreationInput<'static> for Binary {
     fn into_program_creation_input(self) -> ProgramCreationInput<'static> {
         ProgramCreationInput::Binary {
             data: self,
+            reflect_builtins: false,
         }
     }
 }
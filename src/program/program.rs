@@ -9,8 +9,11 @@ use backend::Facade;
 use context::Context;
 use ContextExt;
 
-use std::{ffi, fmt, mem};
+use std::{ffi, fmt, mem, ptr, slice, str};
+use std::ascii::AsciiExt;
 use std::error::Error;
+use std::ops::Deref;
+use std::hash::{Hash, Hasher};
 use std::collections::hash_state::DefaultState;
 use std::collections::hash_map::{self, HashMap};
 use std::default::Default;
@@ -18,25 +21,36 @@ use std::rc::Rc;
 use std::cell::RefCell;
 use util::FnvHasher;
 
+use fbo;
 use GlObject;
 use Handle;
+use ToGlEnum;
 
 use program::{COMPILER_GLOBAL_LOCK, IntoProgramCreationInput, ProgramCreationInput, Binary};
+use program::{ShaderStage, MissingVersion};
 
-use program::reflection::{Uniform, UniformBlock};
-use program::reflection::{Attribute, TransformFeedbackVarying, TransformFeedbackMode};
+use uniforms::{IntoUniformValue, UniformValue, UniformType};
+use vertex::AttributeType;
+
+use program::reflection::{NameBuffer, ReflectionMap, Uniform, UniformBlock, UniformBlockMember};
+use program::reflection::{Attribute, Output, TransformFeedbackVarying, TransformFeedbackMode};
+use program::reflection::{ImageUniform, SamplerKind, is_image, sampler_kind};
 use program::reflection::{reflect_uniforms, reflect_attributes, reflect_uniform_blocks};
-use program::reflection::{reflect_transform_feedback};
-use program::shader::build_shader;
+use program::reflection::{reflect_outputs, program_interface_query_id, StageMask};
+use program::reflection::{reflect_transform_feedback, reflect_uniform_defaults};
+use program::combined;
+use program::defines::{inject_defines, inject_default_precision, inject_version};
+use program::shader::{Shader, annotate_log, build_shader, build_spirv_shader};
+use program::shader::{build_binary_shader, glenum_to_shader_stage, strip_bom};
 
 /// Error that can be triggered when creating a `Program`.
 #[derive(Clone, Debug)]
 pub enum ProgramCreationError {
     /// Error while compiling one of the shaders.
-    CompilationError(String),
+    CompilationError(ShaderError),
 
     /// Error while linking the program.
-    LinkingError(String),
+    LinkingError(ShaderError),
 
     /// One of the requested shader types is not supported by the backend.
     ///
@@ -44,11 +58,50 @@ pub enum ProgramCreationError {
     ShaderTypeNotSupported,
 
     /// The OpenGL implementation doesn't provide a compiler.
+    ///
+    /// This is the case when `GL_SHADER_COMPILER` is false, which happens with some OpenGL ES
+    /// drivers that only accept binaries. Creating a program from source code then fails
+    /// immediately with this error.
     CompilationNotSupported,
 
     /// You have requested transform feedback varyings, but transform feedback is not supported
     /// by the backend.
     TransformFeedbackNotSupported,
+
+    /// The backend failed to create the program object.
+    ///
+    /// This can happen if the GPU is out of memory or if the context has been lost.
+    CreationFailed,
+
+    /// The operation requires the source code of the program, but the program was not
+    /// created from source code, or its source code was not retained.
+    SourceNotAvailable,
+
+    /// The backend doesn't support retrieving the binary of a program.
+    BinaryNotSupported,
+
+    /// You have requested indexed fragment data locations, but dual-source blending is not
+    /// supported by the backend.
+    DualSourceBlendingNotSupported,
+
+    /// The program has been linked, but querying its uniforms, attributes, outputs, blocks
+    /// or varyings failed.
+    ///
+    /// This usually happens because of a driver bug, for example a driver that returns an
+    /// invalid type or a truncated name. Contains a description of the problem.
+    ReflectionFailed(String),
+
+    /// The `source_transform` function returned an error, which is contained in this variant.
+    ///
+    /// This happens before any shader is compiled.
+    PreprocessingError(String),
+
+    /// The source code of a stage contains an `#extension` directive with the `require`
+    /// behavior, but the extension is not supported by the backend. Contains the stage and the
+    /// name of the extension.
+    ///
+    /// Directives with the `enable` or `warn` behavior are not checked.
+    ExtensionNotSupported(ShaderStage, String),
 }
 
 impl fmt::Display for ProgramCreationError {
@@ -66,6 +119,26 @@ impl fmt::Display for ProgramCreationError {
             &ProgramCreationError::TransformFeedbackNotSupported => 
                 formatter.write_str("You requested transform feedback, but this feature is not \
                                      supported by the backend"),
+            &ProgramCreationError::CreationFailed =>
+                formatter.write_str("The backend failed to create the program object"),
+            &ProgramCreationError::SourceNotAvailable =>
+                formatter.write_str("The source code of the program is not available"),
+            &ProgramCreationError::BinaryNotSupported =>
+                formatter.write_str("The backend doesn't support retrieving the binary of \
+                                     a program"),
+            &ProgramCreationError::DualSourceBlendingNotSupported =>
+                formatter.write_str("You requested indexed fragment data locations, but \
+                                     dual-source blending is not supported by the backend"),
+            &ProgramCreationError::ReflectionFailed(ref s) =>
+                formatter.write_fmt(format_args!("Error while querying the reflection data \
+                                                  of the program: {}", s)),
+            &ProgramCreationError::PreprocessingError(ref s) =>
+                formatter.write_fmt(format_args!("Error while preprocessing the source code: {}",
+                                                 s)),
+            &ProgramCreationError::ExtensionNotSupported(stage, ref ext) =>
+                formatter.write_fmt(format_args!("The {} requires the `{}` extension, which is \
+                                                  not supported by the backend",
+                                                 shader_type_name(stage.to_glenum()), ext)),
         }
     }
 }
@@ -82,24 +155,431 @@ impl Error for ProgramCreationError {
                                                                shaders compilation",
             &ProgramCreationError::TransformFeedbackNotSupported => "Transform feedback is not \
                                                                      supported by the backend.",
+            &ProgramCreationError::CreationFailed => "The backend failed to create the program \
+                                                      object",
+            &ProgramCreationError::SourceNotAvailable => "The source code of the program is not \
+                                                          available",
+            &ProgramCreationError::BinaryNotSupported => "The backend doesn't support \
+                                                          retrieving the binary of a program",
+            &ProgramCreationError::DualSourceBlendingNotSupported => "Dual-source blending is \
+                                                                      not supported by the \
+                                                                      backend",
+            &ProgramCreationError::ReflectionFailed(_) => "Error while querying the reflection \
+                                                           data of the program",
+            &ProgramCreationError::PreprocessingError(_) => "Error while preprocessing the source \
+                                                             code",
+            &ProgramCreationError::ExtensionNotSupported(_, _) => "A shader requires an \
+                                                                   extension that is not \
+                                                                   supported by the backend",
         }
     }
 
     fn cause(&self) -> Option<&Error> {
-        None
+        match self {
+            &ProgramCreationError::CompilationError(ref e) => Some(e as &Error),
+            &ProgramCreationError::LinkingError(ref e) => Some(e as &Error),
+            _ => None
+        }
+    }
+}
+
+/// Error reported by the compiler or the linker.
+///
+/// Whether the error happened while compiling or while linking is given by the variant of
+/// `ProgramCreationError` that contains it.
+///
+/// Dereferences to the log, so it can be used like a string.
+#[derive(Clone, Debug)]
+pub struct ShaderError {
+    /// The stage that the error is about, if it is known.
+    ///
+    /// This is purely informational. A linking error can be about a specific stage, for
+    /// example a geometry shader whose `max_vertices` is too large, and an error can happen
+    /// before the source code is split between stages. If multiple stages failed to compile,
+    /// this is the first one and the log contains the errors of all of them.
+    pub stage: Option<ShaderStage>,
+
+    /// The log of the compiler or the linker. Its content is implementation-defined.
+    pub log: String,
+}
+
+impl Deref for ShaderError {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.log
+    }
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        self.log.fmt(formatter)
+    }
+}
+
+impl Error for ShaderError {
+    fn description(&self) -> &str {
+        "Error reported by the compiler or the linker"
+    }
+}
+
+/// Configuration of a vertex attribute, as returned by `Program::build_attribute_bindings`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AttribBinding {
+    /// Name of the attribute.
+    pub name: String,
+
+    /// Location of the attribute, to pass to `glVertexAttribPointer`.
+    pub location: i32,
+
+    /// Number of components of the attribute.
+    pub num_components: usize,
+
+    /// Type of the attribute in the shader.
+    pub ty: AttributeType,
+}
+
+/// Error that can be returned by `Program::build_attribute_bindings`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BindingError {
+    /// The vertex format contains an attribute that the program doesn't have.
+    UnknownAttribute(String),
+
+    /// The program has an attribute that is missing from the vertex format.
+    MissingAttribute(String),
+
+    /// The number of components of an attribute in the vertex format doesn't match the type
+    /// of the attribute in the program.
+    ComponentCountMismatch {
+        /// Name of the attribute.
+        name: String,
+        /// Number of components in the program.
+        expected: usize,
+        /// Number of components in the vertex format.
+        obtained: usize,
+    },
+}
+
+impl fmt::Display for BindingError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            &BindingError::UnknownAttribute(ref name) =>
+                formatter.write_fmt(format_args!("The program has no attribute named `{}`", name)),
+            &BindingError::MissingAttribute(ref name) =>
+                formatter.write_fmt(format_args!("The attribute `{}` is missing from the vertex \
+                                                  format", name)),
+            &BindingError::ComponentCountMismatch { ref name, expected, obtained } =>
+                formatter.write_fmt(format_args!("The attribute `{}` has {} components in the \
+                                                  program but {} in the vertex format",
+                                                 name, expected, obtained)),
+        }
+    }
+}
+
+impl Error for BindingError {
+    fn description(&self) -> &str {
+        match self {
+            &BindingError::UnknownAttribute(_) => "The program has no attribute with this name",
+            &BindingError::MissingAttribute(_) => "An attribute of the program is missing from \
+                                                   the vertex format",
+            &BindingError::ComponentCountMismatch { .. } => "The number of components of an \
+                                                             attribute doesn't match",
+        }
+    }
+}
+
+/// Error returned by `Program::check_attribute`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AttributeMismatch {
+    /// Name of the attribute.
+    pub name: String,
+
+    /// The type that was expected by the caller.
+    pub expected: AttributeType,
+
+    /// The type of the attribute in the program, or `None` if the program has no active
+    /// attribute with this name.
+    pub actual: Option<AttributeType>,
+}
+
+impl fmt::Display for AttributeMismatch {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self.actual {
+            Some(actual) => formatter.write_fmt(format_args!("The attribute `{}` has the type \
+                                                              {:?} in the program, but {:?} \
+                                                              was expected", self.name,
+                                                             actual, self.expected)),
+            None => formatter.write_fmt(format_args!("The program has no attribute named `{}`",
+                                                     self.name)),
+        }
+    }
+}
+
+impl Error for AttributeMismatch {
+    fn description(&self) -> &str {
+        match self.actual {
+            Some(_) => "The type of the attribute doesn't match",
+            None => "The program has no attribute with this name",
+        }
+    }
+}
+
+/// Receives the reflection data of a program.
+///
+/// See `Program::accept_reflection_visitor`. All the methods do nothing by default, so that
+/// you only need to implement the ones you are interested in.
+pub trait ProgramReflectionVisitor {
+    /// Called for each active uniform, including the ones that are images or samplers.
+    #[allow(unused_variables)]
+    fn visit_uniform(&mut self, name: &str, uniform: &Uniform) {}
+
+    /// Called for each active attribute.
+    #[allow(unused_variables)]
+    fn visit_attribute(&mut self, name: &str, attribute: &Attribute) {}
+
+    /// Called for each uniform block, before its members.
+    #[allow(unused_variables)]
+    fn visit_block(&mut self, name: &str, block: &UniformBlock) {}
+
+    /// Called for each member of a uniform block, right after the block.
+    #[allow(unused_variables)]
+    fn visit_block_member(&mut self, block_name: &str, member: &UniformBlockMember) {}
+
+    /// Called for each transform feedback varying, in the order of the buffer.
+    #[allow(unused_variables)]
+    fn visit_varying(&mut self, varying: &TransformFeedbackVarying) {}
+}
+
+/// Error returned by `Program::set_uniform` and `Program::set_uniform_checked`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UniformTypeMismatch {
+    /// Name of the uniform.
+    pub name: String,
+
+    /// The type of the uniform in the program, or `None` if the program has no active uniform
+    /// with this name.
+    pub expected: Option<UniformType>,
+
+    /// The type of the value, or `None` if the value is a texture or a buffer, which can only
+    /// be used when drawing.
+    pub obtained: Option<UniformType>,
+}
+
+impl fmt::Display for UniformTypeMismatch {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match (self.expected, self.obtained) {
+            (None, _) => formatter.write_fmt(format_args!("The program has no uniform named \
+                                                           `{}`", self.name)),
+            (Some(_), None) => formatter.write_fmt(format_args!("The value of uniform `{}` can \
+                                                                 only be set when drawing",
+                                                                self.name)),
+            (Some(expected), Some(obtained)) => {
+                formatter.write_fmt(format_args!("The uniform `{}` has the type {:?} in the \
+                                                  program, but the value has the type {:?}",
+                                                 self.name, expected, obtained))
+            },
+        }
+    }
+}
+
+impl Error for UniformTypeMismatch {
+    fn description(&self) -> &str {
+        match (self.expected, self.obtained) {
+            (None, _) => "The program has no uniform with this name",
+            (Some(_), None) => "The value can only be set when drawing",
+            (Some(_), Some(_)) => "The type of the value doesn't match the type of the uniform",
+        }
+    }
+}
+
+/// Error returned by `Program::validate`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValidationError {
+    /// The driver reported that the program can't be executed with the current state.
+    Invalid {
+        /// Value of `GL_VALIDATE_STATUS` as returned by the driver. Any value other than
+        /// `GL_TRUE` is considered a failure. This is `GL_FALSE`, ie. `0`, unless the driver is
+        /// buggy, but it is available so that it can be logged.
+        status: i32,
+
+        /// The info log written by the driver. Some drivers leave it empty.
+        log: String,
+    },
+
+    /// The backend doesn't support validating programs.
+    Unsupported,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            &ValidationError::Invalid { status, ref log } =>
+                formatter.write_fmt(format_args!("The program failed validation \
+                                                  (GL_VALIDATE_STATUS = {}): {}", status, log)),
+            &ValidationError::Unsupported =>
+                formatter.write_str("The backend doesn't support validating programs"),
+        }
+    }
+}
+
+impl Error for ValidationError {
+    fn description(&self) -> &str {
+        match self {
+            &ValidationError::Invalid { .. } => "The program failed validation",
+            &ValidationError::Unsupported => "The backend doesn't support validating programs",
+        }
     }
 }
 
 /// A combination of shaders linked together.
+///
+/// # Multithreading
+///
+/// A `Program` is bound to the context that created it and can't be sent to another thread,
+/// even if this context shares its objects with a context of the other thread. glium keeps
+/// track of the state of each context, and this tracking would be wrong if a program was
+/// used or destroyed through a different context.
+///
+/// To compile programs in the background, for example during a loading screen, create a
+/// second context that shares its lists with the main one in a worker thread. Then build the
+/// program with this context, retrieve its binary with `get_binary_if_supported` and send the
+/// `Binary` to the main thread, which loads it with `Program::new`. Loading a binary is much
+/// faster than compiling the source code. The binary is only valid for the driver that
+/// produced it, which is always the case for two contexts of the same process.
 pub struct Program {
     context: Rc<Context>,
     id: Handle,
-    uniforms: HashMap<String, Uniform, DefaultState<FnvHasher>>,
-    uniform_blocks: HashMap<String, UniformBlock, DefaultState<FnvHasher>>,
-    attributes: HashMap<String, Attribute, DefaultState<FnvHasher>>,
-    frag_data_locations: RefCell<HashMap<String, Option<u32>, DefaultState<FnvHasher>>>,
+    uniforms: ReflectionMap<Uniform>,
+    uniform_defaults: ReflectionMap<UniformValue<'static>>,
+    uniform_blocks: ReflectionMap<UniformBlock>,
+    attributes: ReflectionMap<Attribute>,
+    outputs: ReflectionMap<Output>,
+    frag_data_locations: RefCell<ReflectionMap<Option<u32>>>,
     varyings: Option<(Vec<TransformFeedbackVarying>, TransformFeedbackMode)>,
     has_tessellation_shaders: bool,
+    source: Option<RetainedSource>,
+    source_hash: Option<u64>,
+    early_fragment_tests: bool,
+    writes_point_size: bool,
+    link_log: Option<String>,
+    context_info: ContextInfo,
+    reflect: bool,
+    reflect_builtins: bool,
+}
+
+/// Summary of the features of the context that were available when a program was created.
+///
+/// The features that are listed here change the way glium creates and uses programs. This is
+/// mostly useful for bug reports.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ContextInfo {
+    /// Version of the context.
+    pub version: Version,
+
+    /// True if transform feedback is supported.
+    pub transform_feedback: bool,
+
+    /// True if program binaries can be retrieved and loaded.
+    pub program_binary: bool,
+
+    /// True if separate shader objects are supported.
+    pub separate_shader_objects: bool,
+
+    /// True if the program interface query API is supported. This is required to reflect
+    /// the outputs of a program.
+    pub program_interface_query: bool,
+
+    /// True if uniform blocks are supported.
+    pub uniform_buffer_object: bool,
+
+    /// True if geometry shaders are supported.
+    pub geometry_shader: bool,
+
+    /// True if tessellation shaders are supported.
+    pub tessellation_shader: bool,
+
+    /// True if SPIR-V modules are supported.
+    pub spirv: bool,
+
+    /// True if compute shaders are supported.
+    pub compute_shader: bool,
+}
+
+impl ContextInfo {
+    fn from_context(ctxt: &CommandContext) -> ContextInfo {
+        let version = ctxt.version;
+        let extensions = ctxt.extensions;
+
+        ContextInfo {
+            version: *version,
+            transform_feedback: version >= &Version(Api::Gl, 3, 0) ||
+                                version >= &Version(Api::GlEs, 3, 0) ||
+                                extensions.gl_ext_transform_feedback,
+            program_binary: version >= &Version(Api::Gl, 4, 1) ||
+                            version >= &Version(Api::GlEs, 3, 0) ||
+                            extensions.gl_arb_get_programy_binary,
+            separate_shader_objects: version >= &Version(Api::Gl, 4, 1) ||
+                                     version >= &Version(Api::GlEs, 3, 1) ||
+                                     extensions.gl_arb_separate_shader_objects ||
+                                     (version.0 == Api::GlEs &&
+                                      extensions.gl_ext_separate_shader_objects),
+            program_interface_query: version >= &Version(Api::Gl, 4, 3) ||
+                                     version >= &Version(Api::GlEs, 3, 1) ||
+                                     extensions.gl_arb_program_interface_query,
+            uniform_buffer_object: version >= &Version(Api::Gl, 3, 1) ||
+                                   version >= &Version(Api::GlEs, 3, 0) ||
+                                   extensions.gl_arb_uniform_buffer_object,
+            geometry_shader: version >= &Version(Api::Gl, 3, 2) ||
+                             extensions.gl_arb_geometry_shader4 ||
+                             extensions.gl_ext_geometry_shader4,
+            tessellation_shader: version >= &Version(Api::Gl, 4, 0) ||
+                                 extensions.gl_arb_tessellation_shader,
+            spirv: version >= &Version(Api::Gl, 4, 6) || extensions.gl_arb_gl_spirv,
+            compute_shader: version >= &Version(Api::Gl, 4, 3) ||
+                            version >= &Version(Api::GlEs, 3, 1) ||
+                            extensions.gl_arb_compute_shader,
+        }
+    }
+}
+
+/// A feature that may or may not be usable with a program.
+///
+/// See `Program::supports_feature`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ProgramFeature {
+    /// Retrieving the binary of the program with `get_binary_if_supported`.
+    BinaryRetrieval,
+    /// Capturing the outputs of the program with transform feedback.
+    TransformFeedback,
+    /// Dispatching compute shaders.
+    Compute,
+    /// Using geometry shaders.
+    Geometry,
+    /// Using tessellation shaders.
+    Tessellation,
+    /// Linking programs that contain only some stages of the pipeline, and setting uniforms
+    /// without binding the program.
+    SeparableLinkage,
+    /// Using uniform blocks.
+    UniformBlocks,
+    /// Reflecting the outputs of the program with the program interface query API.
+    InterfaceQuery,
+    /// Building programs from SPIR-V modules.
+    SpirV,
+}
+
+/// Copy of the source code of a program, kept in order to be able to recompile it.
+#[derive(Clone)]
+struct RetainedSource {
+    vertex_shader: String,
+    tessellation_control_shader: Option<String>,
+    tessellation_evaluation_shader: Option<String>,
+    geometry_shader: Option<String>,
+    fragment_shader: String,
+    transform_feedback_varyings: Option<(Vec<String>, TransformFeedbackMode)>,
+    indexed_frag_data_locations: Option<Vec<(String, u32, u32)>>,
+    default_version: Option<String>,
+    strict: bool,
 }
 
 impl Program {
@@ -107,12 +587,75 @@ impl Program {
     pub fn new<'a, F, I>(facade: &F, input: I) -> Result<Program, ProgramCreationError>
                          where I: IntoProgramCreationInput<'a>, F: Facade
     {
-        let input = input.into_program_creation_input();
+        Program::new_impl(facade, input.into_program_creation_input(), true)
+    }
 
-        if let ProgramCreationInput::SourceCode { .. } = input {
-            Program::from_source_impl(facade, input)
-        } else {
-            Program::from_binary_impl(facade, input)
+    /// Builds a new program without querying the list of its uniforms, attributes, uniform
+    /// blocks and transform feedback varyings.
+    ///
+    /// This is faster than `new` and uses less memory, which is useful when loading a large
+    /// number of programs. However the program will behave as if it had no uniform, attribute,
+    /// block or varying at all. For example `get_uniform` always returns `None`, and uniforms
+    /// passed when drawing are ignored. This means that you have to use raw locations
+    /// to drive the program.
+    pub fn new_without_reflection<'a, F, I>(facade: &F, input: I)
+                                            -> Result<Program, ProgramCreationError>
+                                            where I: IntoProgramCreationInput<'a>, F: Facade
+    {
+        Program::new_impl(facade, input.into_program_creation_input(), false)
+    }
+
+    /// Builds a new program from a binary if possible, or from `source` otherwise.
+    ///
+    /// This is the usual way to use a cache of binaries. The binary is only loaded if its
+    /// format is supported by the backend. If it can't be loaded, for example because the
+    /// driver has been updated since it was produced, the program is built from `source`
+    /// instead and the failed program object is destroyed.
+    ///
+    /// Returns the program and `true` if it was loaded from the binary.
+    pub fn from_binary_or_source<'a, F, I>(facade: &F, binary: Option<Binary>, source: I)
+                                           -> Result<(Program, bool), ProgramCreationError>
+                                           where I: IntoProgramCreationInput<'a>, F: Facade
+    {
+        let source = source.into_program_creation_input();
+
+        let reflect_builtins = match &source {
+            &ProgramCreationInput::SourceCode { reflect_builtins, .. } => reflect_builtins,
+            &ProgramCreationInput::Binary { reflect_builtins, .. } => reflect_builtins,
+            &ProgramCreationInput::Binaries { reflect_builtins, .. } => reflect_builtins,
+            &ProgramCreationInput::SpirV { reflect_builtins, .. } => reflect_builtins,
+            &ProgramCreationInput::ShaderBinaries { reflect_builtins, .. } => reflect_builtins,
+        };
+
+        if let Some(binary) = binary {
+            if binary.is_format_supported(facade) {
+                if let Ok(program) = Program::from_single_binary(facade, &binary, true,
+                                                                 reflect_builtins)
+                {
+                    return Ok((program, true));
+                }
+            }
+        }
+
+        Program::new_impl(facade, source, true).map(|program| (program, false))
+    }
+
+    fn new_impl<F>(facade: &F, input: ProgramCreationInput, reflect: bool)
+                   -> Result<Program, ProgramCreationError> where F: Facade
+    {
+        match input {
+            ProgramCreationInput::SourceCode { .. } => {
+                Program::from_source_impl(facade, input, reflect, None)
+            },
+            ProgramCreationInput::Binary { .. } | ProgramCreationInput::Binaries { .. } => {
+                Program::from_binary_impl(facade, input, reflect)
+            },
+            ProgramCreationInput::SpirV { .. } => {
+                Program::from_spirv_impl(facade, input, reflect)
+            },
+            ProgramCreationInput::ShaderBinaries { .. } => {
+                Program::from_shader_binaries_impl(facade, input, reflect)
+            },
         }
     }
 
@@ -147,33 +690,263 @@ impl Program {
             tessellation_control_shader: None,
             tessellation_evaluation_shader: None,
             transform_feedback_varyings: None,
-        })
+            indexed_frag_data_locations: None,
+            source_transform: None,
+            retain_link_log: false,
+            retain_source: false,
+            strict: false,
+            missing_version: MissingVersion::Ignore,
+            reflect_builtins: false,
+        }, true, None)
+    }
+
+    /// Builds a new program from a single source file that contains multiple stages.
+    ///
+    /// Each stage starts with a `#pragma stage <name>` line, where `<name>` is `vertex`,
+    /// `tessellation_control`, `tessellation_evaluation`, `geometry` or `fragment`. The vertex
+    /// and fragment stages are mandatory. Everything before the first `#pragma stage`,
+    /// including the `#version` directive, is shared by all the stages.
+    ///
+    /// The line numbers in the compilation errors are the ones of the combined source.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let display: glium::Display = unsafe { std::mem::uninitialized() };
+    /// let program = glium::Program::from_combined_source(&display, "
+    ///     #version 110
+    ///
+    ///     #pragma stage vertex
+    ///     void main() {
+    ///         gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+    ///     }
+    ///
+    ///     #pragma stage fragment
+    ///     void main() {
+    ///         gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+    ///     }
+    /// ");
+    /// ```
+    pub fn from_combined_source<F>(facade: &F, source: &str)
+                                   -> Result<Program, ProgramCreationError> where F: Facade
+    {
+        let stages = match combined::split(source) {
+            Ok(s) => s,
+            Err(msg) => return Err(ProgramCreationError::CompilationError(ShaderError {
+                stage: None,
+                log: msg,
+            }))
+        };
+
+        let result = Program::from_source_impl(facade, ProgramCreationInput::SourceCode {
+            vertex_shader: &stages.vertex_shader,
+            tessellation_control_shader: stages.tessellation_control_shader.as_ref()
+                                               .map(|s| &s[..]),
+            tessellation_evaluation_shader: stages.tessellation_evaluation_shader.as_ref()
+                                                  .map(|s| &s[..]),
+            geometry_shader: stages.geometry_shader.as_ref().map(|s| &s[..]),
+            fragment_shader: &stages.fragment_shader,
+            transform_feedback_varyings: None,
+            indexed_frag_data_locations: None,
+            source_transform: None,
+            retain_link_log: false,
+            retain_source: false,
+            strict: false,
+            missing_version: MissingVersion::Ignore,
+            reflect_builtins: false,
+        }, true, None);
+
+        // the line numbers of the errors are the ones of the combined source
+        match result {
+            Err(ProgramCreationError::CompilationError(err)) => {
+                Err(ProgramCreationError::CompilationError(ShaderError {
+                    stage: err.stage,
+                    log: annotate_log(&err.log, source),
+                }))
+            },
+            r => r
+        }
+    }
+
+    /// Same as `from_source`, but also returns the warnings emitted by the compiler and the
+    /// linker.
+    ///
+    /// The info logs of the shaders and of the program are split into lines, and the lines
+    /// that are obviously not warnings, like empty lines or success messages, are dropped.
+    /// The format of the warnings is implementation-defined.
+    pub fn from_source_verbose<'a, F>(facade: &F, vertex_shader: &'a str,
+                                      fragment_shader: &'a str, geometry_shader: Option<&'a str>)
+                                      -> Result<(Program, Vec<String>), ProgramCreationError>
+                                      where F: Facade
+    {
+        let mut warnings = Vec::new();
+
+        let program = try!(Program::from_source_impl(facade, ProgramCreationInput::SourceCode {
+            vertex_shader: vertex_shader,
+            fragment_shader: fragment_shader,
+            geometry_shader: geometry_shader,
+            tessellation_control_shader: None,
+            tessellation_evaluation_shader: None,
+            transform_feedback_varyings: None,
+            indexed_frag_data_locations: None,
+            source_transform: None,
+            retain_link_log: false,
+            retain_source: false,
+            strict: false,
+            missing_version: MissingVersion::Ignore,
+            reflect_builtins: false,
+        }, true, Some(&mut warnings)));
+
+        Ok((program, warnings))
+    }
+
+    /// Builds a new program by linking together shaders that have already been compiled.
+    ///
+    /// The same `Shader` can be used to build multiple programs, which avoids compiling
+    /// its source code multiple times.
+    pub fn from_shaders<F>(facade: &F, shaders: &[&Shader])
+                           -> Result<Program, ProgramCreationError> where F: Facade
+    {
+        Program::link_shaders(facade, shaders, None, &[], true, false, None)
+    }
+
+    /// Compiles all the shaders of `input` without linking them together, and returns the
+    /// first compilation error.
+    ///
+    /// The shaders are destroyed afterwards. This is useful to quickly check whether shaders
+    /// compile, for example in a build tool. Since there is no linking, errors that involve
+    /// multiple stages are not detected.
+    ///
+    /// Program binaries can't be checked without linking and are always considered valid.
+    pub fn precompile_shaders<'a, F, I>(facade: &F, input: I) -> Result<(), ProgramCreationError>
+                                        where I: IntoProgramCreationInput<'a>, F: Facade
+    {
+        match input.into_program_creation_input() {
+            ProgramCreationInput::SourceCode { vertex_shader, fragment_shader, geometry_shader,
+                                               tessellation_control_shader,
+                                               tessellation_evaluation_shader, .. } =>
+            {
+                let shaders = [
+                    (Some(vertex_shader), gl::VERTEX_SHADER),
+                    (tessellation_control_shader, gl::TESS_CONTROL_SHADER),
+                    (tessellation_evaluation_shader, gl::TESS_EVALUATION_SHADER),
+                    (geometry_shader, gl::GEOMETRY_SHADER),
+                    (Some(fragment_shader), gl::FRAGMENT_SHADER),
+                ];
+
+                for &(src, ty) in shaders.iter() {
+                    if let Some(src) = src {
+                        try!(build_shader(facade, ty, src));
+                    }
+                }
+            },
+
+            ProgramCreationInput::SpirV { stages, specialization_constants, .. } => {
+                for &(stage, binary, entry_point) in stages.iter() {
+                    try!(build_spirv_shader(facade, stage.to_glenum(), binary, entry_point,
+                                            specialization_constants));
+                }
+            },
+
+            ProgramCreationInput::ShaderBinaries { stages, .. } => {
+                for &(stage, format, binary) in stages.iter() {
+                    try!(build_binary_shader(facade, stage.to_glenum(), format, binary));
+                }
+            },
+
+            ProgramCreationInput::Binary { .. } | ProgramCreationInput::Binaries { .. } => ()
+        }
+
+        Ok(())
     }
 
     /// Compiles a program from source.
     ///
     /// Must only be called if `input` is a `ProgramCreationInput::SourceCode`, will
     /// panic otherwise.
-    fn from_source_impl<F>(facade: &F, input: ProgramCreationInput)
+    ///
+    /// If `warnings` is `Some`, the warnings of the compiler and the linker are added to it.
+    fn from_source_impl<F>(facade: &F, input: ProgramCreationInput, reflect: bool,
+                           mut warnings: Option<&mut Vec<String>>)
                            -> Result<Program, ProgramCreationError>
                            where F: Facade
     {
-        let mut has_tessellation_shaders = false;
+        // drivers that only accept binaries report `GL_SHADER_COMPILER` as false, in which case
+        // there is no point in preprocessing the source code
+        if !facade.get_context().capabilities().shader_compiler {
+            return Err(ProgramCreationError::CompilationNotSupported);
+        }
+
+        // the heuristics based on the source code are computed now, so that they don't require
+        // the source code to be retained
+        let (early_fragment_tests, writes_point_size) = match input {
+            ProgramCreationInput::SourceCode { vertex_shader, fragment_shader, geometry_shader,
+                                               tessellation_evaluation_shader, .. } =>
+            {
+                let last_stage = geometry_shader.or(tessellation_evaluation_shader)
+                                                .unwrap_or(vertex_shader);
+                (declares_early_fragment_tests(fragment_shader), assigns_point_size(last_stage))
+            },
+            _ => unreachable!()     // the function shouldn't be called with anything else
+        };
+
+        let retained_source = match input {
+            ProgramCreationInput::SourceCode { retain_source: false, .. } => None,
+            ProgramCreationInput::SourceCode { vertex_shader, fragment_shader, geometry_shader,
+                                               tessellation_control_shader,
+                                               tessellation_evaluation_shader,
+                                               ref transform_feedback_varyings,
+                                               indexed_frag_data_locations,
+                                               strict, missing_version, .. } =>
+            {
+                Some(RetainedSource {
+                    vertex_shader: vertex_shader.to_string(),
+                    tessellation_control_shader: tessellation_control_shader
+                                                    .map(|s| s.to_string()),
+                    tessellation_evaluation_shader: tessellation_evaluation_shader
+                                                       .map(|s| s.to_string()),
+                    geometry_shader: geometry_shader.map(|s| s.to_string()),
+                    fragment_shader: fragment_shader.to_string(),
+                    transform_feedback_varyings: transform_feedback_varyings.clone(),
+                    indexed_frag_data_locations: indexed_frag_data_locations.map(|l| {
+                        l.iter().map(|&(name, color, index)| (name.to_string(), color, index))
+                         .collect()
+                    }),
+                    default_version: match missing_version {
+                        MissingVersion::Default(v) => Some(v.to_string()),
+                        _ => None
+                    },
+                    strict: strict,
+                })
+            },
+            _ => unreachable!()     // the function shouldn't be called with anything else
+        };
 
         // getting an array of the source codes and their type
-        let (shaders, transform_feedback_varyings): (Vec<(&str, gl::types::GLenum)>, _) = {
+        let (shaders, transform_feedback_varyings, indexed_frag_data_locations,
+             source_transform, retain_link_log, strict, missing_version, reflect_builtins):
+             (Vec<(&str, gl::types::GLenum)>, _, _, _, bool, bool, _, bool) =
+        {
             let (vertex_shader, fragment_shader, geometry_shader,
                  tessellation_control_shader, tessellation_evaluation_shader,
-                 transform_feedback_varyings) = match input
+                 transform_feedback_varyings, indexed_frag_data_locations,
+                 source_transform, retain_link_log, strict, missing_version,
+                 reflect_builtins) = match input
             {
                 ProgramCreationInput::SourceCode { vertex_shader, fragment_shader,
                                                    geometry_shader, tessellation_control_shader,
                                                    tessellation_evaluation_shader,
-                                                   transform_feedback_varyings } =>
+                                                   transform_feedback_varyings,
+                                                   indexed_frag_data_locations,
+                                                   source_transform, retain_link_log,
+                                                   strict, missing_version,
+                                                   reflect_builtins, .. } =>
                 {
                     (vertex_shader, fragment_shader, geometry_shader,
                      tessellation_control_shader, tessellation_evaluation_shader,
-                     transform_feedback_varyings)
+                     transform_feedback_varyings, indexed_frag_data_locations,
+                     source_transform, retain_link_log, strict, missing_version,
+                     reflect_builtins)
                 },
                 _ => unreachable!()     // the function shouldn't be called with anything else
             };
@@ -184,76 +957,328 @@ impl Program {
             ];
 
             if let Some(gs) = geometry_shader {
+                // some drivers give obscure errors when the limit is exceeded, so we check it
+                // ourselves before compiling
+                let max = facade.get_context().capabilities().max_geometry_output_vertices;
+                if let (Some(requested), Some(max)) = (get_geometry_max_vertices(gs), max) {
+                    if requested > max as u32 {
+                        return Err(ProgramCreationError::LinkingError(ShaderError {
+                            stage: Some(ShaderStage::Geometry),
+                            log: format!("The geometry shader declares `max_vertices = {}`, \
+                                          but the maximum supported by the backend is {}",
+                                         requested, max),
+                        }));
+                    }
+                }
+
                 shaders.push((gs, gl::GEOMETRY_SHADER));
             }
 
+            // a tessellation evaluation shader can be used alone, but not a control shader ;
+            // the error returned by the linker is usually not very explicit
+            if tessellation_control_shader.is_some() && tessellation_evaluation_shader.is_none() {
+                return Err(ProgramCreationError::LinkingError(ShaderError {
+                    stage: Some(ShaderStage::TessellationControl),
+                    log: format!("A tessellation control shader was provided without a \
+                                  tessellation evaluation shader. Tessellation requires at \
+                                  least an evaluation shader."),
+                }));
+            }
+
             if let Some(ts) = tessellation_control_shader {
-                has_tessellation_shaders = true;
                 shaders.push((ts, gl::TESS_CONTROL_SHADER));
             }
 
             if let Some(ts) = tessellation_evaluation_shader {
-                has_tessellation_shaders = true;
                 shaders.push((ts, gl::TESS_EVALUATION_SHADER));
             }
 
-            if transform_feedback_varyings.is_some() &&
-                (facade.get_context().get_version() >= &Version(Api::Gl, 3, 0) ||
-                    !facade.get_context().get_extensions().gl_ext_transform_feedback)
+            // transform feedback requires OpenGL 3.0, OpenGL ES 3.0 or
+            // `GL_EXT_transform_feedback`, and can't be used with programs created with
+            // `GL_ARB_shader_objects`, which are identified by handles
+            if transform_feedback_varyings.is_some() {
+                let version = facade.get_context().get_version();
+                let extensions = facade.get_context().get_extensions();
+
+                let core = version >= &Version(Api::Gl, 3, 0) ||
+                           version >= &Version(Api::GlEs, 3, 0);
+
+                if !core && !extensions.gl_ext_transform_feedback {
+                    return Err(ProgramCreationError::TransformFeedbackNotSupported);
+                }
+
+                if !core && !(version >= &Version(Api::Gl, 2, 0)) {
+                    return Err(ProgramCreationError::TransformFeedbackNotSupported);
+                }
+            }
+
+            if indexed_frag_data_locations.is_some() &&
+               !(facade.get_context().get_version() >= &Version(Api::Gl, 3, 3))
             {
-                return Err(ProgramCreationError::TransformFeedbackNotSupported);
+                return Err(ProgramCreationError::DualSourceBlendingNotSupported);
             }
 
-            (shaders, transform_feedback_varyings)
+            (shaders, transform_feedback_varyings, indexed_frag_data_locations.unwrap_or(&[]),
+             source_transform, retain_link_log, strict, missing_version, reflect_builtins)
         };
 
-        let shaders_store = {
-            let mut shaders_store = Vec::new();
-            for (src, ty) in shaders.into_iter() {
-                shaders_store.push(try!(build_shader(facade, ty, src)));
+        // forgetting the `#version` directive is a common mistake that leads to confusing
+        // syntax errors, as the driver then uses GLSL 1.10
+        let mut versioned_sources = Vec::new();
+        for &(src, ty) in shaders.iter() {
+            if get_glsl_version(src).is_some() {
+                versioned_sources.push(None);
+                continue;
             }
-            shaders_store
-        };
 
-        let mut shaders_ids = Vec::new();
-        for sh in shaders_store.iter() {
-            shaders_ids.push(sh.get_id());
+            match missing_version {
+                MissingVersion::Ignore => versioned_sources.push(None),
+                MissingVersion::Error => {
+                    return Err(ProgramCreationError::CompilationError(ShaderError {
+                        stage: glenum_to_shader_stage(ty),
+                        log: format!("The {} is missing a `#version` directive",
+                                     shader_type_name(ty)),
+                    }));
+                },
+                MissingVersion::Default(version) => {
+                    versioned_sources.push(Some(inject_version(src, version)));
+                },
+            }
         }
 
-        let mut ctxt = facade.get_context().make_current();
-
-        let id = unsafe {
-            let id = create_program(&mut ctxt);
+        let shaders = shaders.iter().zip(versioned_sources.iter()).map(|(&(src, ty), v)| {
+            (v.as_ref().map(|v| &v[..]).unwrap_or(src), ty)
+        }).collect::<Vec<_>>();
 
-            // attaching shaders
-            for sh in shaders_ids.iter() {
-                match (id, sh) {
-                    (Handle::Id(id), &Handle::Id(sh)) => {
-                        assert!(ctxt.version >= &Version(Api::Gl, 2, 0));
-                        ctxt.gl.AttachShader(id, sh);
-                    },
-                    (Handle::Handle(id), &Handle::Handle(sh)) => {
-                        assert!(ctxt.extensions.gl_arb_shader_objects);
-                        ctxt.gl.AttachObjectARB(id, sh);
-                    },
-                    _ => unreachable!()
+        // fragment shaders of OpenGL ES don't have a default precision for floats and fail to
+        // compile without one, which is a common problem with shaders written for desktop
+        let precision_sources = if facade.get_context().get_version().0 == Api::GlEs {
+            shaders.iter().map(|&(src, ty)| {
+                if ty == gl::FRAGMENT_SHADER && !declares_float_precision(src) {
+                    Some(inject_default_precision(src))
+                } else {
+                    None
                 }
+            }).collect::<Vec<_>>()
+        } else {
+            Vec::new()
+        };
+
+        let shaders = shaders.iter().enumerate().map(|(num, &(src, ty))| {
+            match precision_sources.get(num) {
+                Some(&Some(ref src)) => (&src[..], ty),
+                _ => (src, ty)
             }
+        }).collect::<Vec<_>>();
+
+        // the user-provided transform is applied last, so that what is analyzed below is
+        // exactly what is compiled
+        let transformed_sources = match source_transform {
+            Some(transform) => {
+                let mut sources = Vec::with_capacity(shaders.len());
+                for &(src, ty) in shaders.iter() {
+                    match transform(glenum_to_shader_stage(ty).unwrap(), src) {
+                        Ok(src) => sources.push(src),
+                        Err(err) => return Err(ProgramCreationError::PreprocessingError(err))
+                    }
+                }
+                Some(sources)
+            },
+            None => None
+        };
 
-            // transform feedback varyings
-            if let Some((names, mode)) = transform_feedback_varyings {
-                let id = match id {
-                    Handle::Id(id) => id,
-                    Handle::Handle(id) => unreachable!()    // transf. feedback shouldn't be
-                                                            // available with handles
-                };
+        let shaders = match transformed_sources {
+            Some(ref sources) => {
+                shaders.iter().zip(sources.iter()).map(|(&(_, ty), src)| (&src[..], ty))
+                       .collect::<Vec<_>>()
+            },
+            None => shaders
+        };
 
-                let names = names.into_iter().map(|name| {
+        // computed from what is passed to the compiler, so that the version and precision
+        // directives and the transform are taken into account
+        let source_hash = hash_sources(&shaders);
+
+        // the error returned by the compiler when a required extension is missing is
+        // driver-specific and often unclear
+        {
+            let supported = &facade.get_context().capabilities().extensions;
+            for &(src, ty) in shaders.iter() {
+                let missing = required_extensions(src).into_iter().find(|ext| {
+                    !supported.iter().any(|s| s == ext)
+                });
+
+                if let Some(ext) = missing {
+                    return Err(ProgramCreationError::ExtensionNotSupported(
+                                                        glenum_to_shader_stage(ty).unwrap(),
+                                                        ext.to_string()));
+                }
+            }
+        }
+
+        // if the shaders don't use the same GLSL version, this will be mentionned in case of
+        // linking error as it is a common mistake
+        let version_mismatch = {
+            let versions = shaders.iter().map(|&(src, ty)| (ty, get_glsl_version(src)))
+                                  .collect::<Vec<_>>();
+
+            versions.iter().skip(1).find(|&&(_, v)| v != versions[0].1).map(|&(ty, v)| {
+                format!("Note: the {} uses `#version {}` but the {} uses `#version {}`",
+                        shader_type_name(versions[0].0), versions[0].1.unwrap_or("110"),
+                        shader_type_name(ty), v.unwrap_or("110"))
+            })
+        };
+
+        // misspelling the name of a transform feedback varying is another common mistake, and
+        // the error returned by the linker is usually not helpful
+        let unknown_varyings = transform_feedback_varyings.as_ref().and_then(|&(ref names, _)| {
+            let last_stage = [gl::GEOMETRY_SHADER, gl::TESS_EVALUATION_SHADER, gl::VERTEX_SHADER]
+                                .iter().filter_map(|&ty| shaders.iter().find(|s| s.1 == ty))
+                                .next();
+
+            let &(src, ty) = match last_stage {
+                Some(s) => s,
+                None => return None
+            };
+
+            let unknown = names.iter().filter(|name| !is_output_declared(src, name))
+                               .map(|name| format!("`{}`", name)).collect::<Vec<_>>();
+
+            if unknown.is_empty() {
+                None
+            } else {
+                Some(format!("Note: the transform feedback varyings {} are not declared as \
+                              outputs of the {}", unknown.connect(", "), shader_type_name(ty)))
+            }
+        });
+
+        let link_error_note = match (version_mismatch, unknown_varyings) {
+            (Some(a), Some(b)) => Some(format!("{}\n{}", a, b)),
+            (a, b) => a.or(b)
+        };
+
+        let inert_stage = if strict { find_inert_stage(&shaders) } else { None };
+
+        let shaders_store = {
+            let mut shaders_store = Vec::new();
+            let mut errors = Vec::new();
+
+            for (src, ty) in shaders.into_iter() {
+                match build_shader(facade, ty, src) {
+                    Ok(shader) => shaders_store.push(shader),
+                    // the other stages are still compiled, so that all the errors are reported
+                    // at once
+                    Err(ProgramCreationError::CompilationError(err)) => errors.push(err),
+                    Err(err) => return Err(err),
+                }
+            }
+
+            if !errors.is_empty() {
+                return Err(ProgramCreationError::CompilationError(merge_shader_errors(errors)));
+            }
+
+            shaders_store
+        };
+
+        if let Some(ref mut warnings) = warnings {
+            for shader in shaders_store.iter() {
+                warnings.extend(parse_log_warnings(&shader.get_info_log()).into_iter());
+            }
+        }
+
+        let shaders_refs = shaders_store.iter().collect::<Vec<_>>();
+        let mut program = try!(Program::link_shaders(facade, &shaders_refs,
+                                                     transform_feedback_varyings,
+                                                     indexed_frag_data_locations, reflect,
+                                                     reflect_builtins, link_error_note));
+
+        // only checked after linking, so that actual errors are reported first
+        if let Some((ty, reason)) = inert_stage {
+            return Err(ProgramCreationError::LinkingError(ShaderError {
+                stage: glenum_to_shader_stage(ty),
+                log: format!("The {} {}", shader_type_name(ty), reason),
+            }));
+        }
+
+        program.source = retained_source;
+        program.source_hash = Some(source_hash);
+        program.early_fragment_tests = early_fragment_tests;
+        program.writes_point_size = writes_point_size;
+
+        if retain_link_log {
+            program.store_link_log();
+        }
+
+        if let Some(ref mut warnings) = warnings {
+            let log = {
+                let mut ctxt = program.context.make_current();
+                unsafe { get_program_info_log(&mut ctxt, program.id) }
+            };
+
+            warnings.extend(parse_log_warnings(&log).into_iter());
+        }
+
+        Ok(program)
+    }
+
+    /// Attaches already-compiled shaders to a new program and links it.
+    ///
+    /// `link_error_note` is a note to add to the error message in case of linking error.
+    fn link_shaders<F>(facade: &F, shaders: &[&Shader],
+                       transform_feedback_varyings: Option<(Vec<String>, TransformFeedbackMode)>,
+                       indexed_frag_data_locations: &[(&str, u32, u32)],
+                       reflect: bool, reflect_builtins: bool, link_error_note: Option<String>)
+                       -> Result<Program, ProgramCreationError> where F: Facade
+    {
+        let has_tessellation_shaders = shaders.iter().any(|sh| {
+            sh.get_stage() == ShaderStage::TessellationControl ||
+            sh.get_stage() == ShaderStage::TessellationEvaluation
+        });
+
+        let mut shaders_ids = Vec::new();
+        for sh in shaders.iter() {
+            shaders_ids.push(sh.get_id());
+        }
+
+        let report_debug_output = facade.get_context().get_report_debug_output_during_linking();
+
+        let mut ctxt = facade.get_context().make_current();
+
+        let id = unsafe {
+            let id = try!(create_program(&mut ctxt));
+
+            // attaching shaders
+            for sh in shaders_ids.iter() {
+                match (id, sh) {
+                    (Handle::Id(id), &Handle::Id(sh)) => {
+                        assert!(ctxt.version >= &Version(Api::Gl, 2, 0));
+                        ctxt.gl.AttachShader(id, sh);
+                    },
+                    (Handle::Handle(id), &Handle::Handle(sh)) => {
+                        assert!(ctxt.extensions.gl_arb_shader_objects);
+                        ctxt.gl.AttachObjectARB(id, sh);
+                    },
+                    _ => unreachable!()
+                }
+            }
+
+            // transform feedback varyings
+            if let Some((names, mode)) = transform_feedback_varyings {
+                let id = match id {
+                    Handle::Id(id) => id,
+                    Handle::Handle(_) => unreachable!("transform feedback can't be used with \
+                                                       handles, this has been checked in the \
+                                                       frontend")
+                };
+
+                let names = names.into_iter().map(|name| {
                     ffi::CString::new(name.into_bytes()).unwrap()
                 }).collect::<Vec<_>>();
                 let names_ptr = names.iter().map(|n| n.as_ptr()).collect::<Vec<_>>();
 
-                if ctxt.version >= &Version(Api::Gl, 3, 0) {
+                if ctxt.version >= &Version(Api::Gl, 3, 0) ||
+                   ctxt.version >= &Version(Api::GlEs, 3, 0)
+                {
                     let mode = match mode {
                         TransformFeedbackMode::Interleaved => gl::INTERLEAVED_ATTRIBS,
                         TransformFeedbackMode::Separate => gl::SEPARATE_ATTRIBS,
@@ -277,11 +1302,22 @@ impl Program {
                 }
             }
 
+            // fragment data locations, which have been checked in the frontend
+            for &(name, color, index) in indexed_frag_data_locations.iter() {
+                let id = match id {
+                    Handle::Id(id) => id,
+                    Handle::Handle(_) => unreachable!()
+                };
+
+                let name = ffi::CString::new(name.as_bytes()).unwrap();
+                ctxt.gl.BindFragDataLocationIndexed(id, color, index, name.as_ptr());
+            }
+
             // linking
             {
                 let _lock = COMPILER_GLOBAL_LOCK.lock();
 
-                ctxt.report_debug_output_errors.set(false);
+                ctxt.report_debug_output_errors.set(report_debug_output);
 
                 match id {
                     Handle::Id(id) => {
@@ -298,50 +1334,110 @@ impl Program {
             }
 
             // checking for errors
-            try!(check_program_link_errors(&mut ctxt, id));
+            match (check_program_link_errors(&mut ctxt, id), link_error_note) {
+                (Err(ProgramCreationError::LinkingError(err)), Some(note)) => {
+                    return Err(ProgramCreationError::LinkingError(ShaderError {
+                        stage: err.stage,
+                        log: format!("{}\n{}", err.log, note),
+                    }));
+                },
+                (Err(e), _) => return Err(e),
+                (Ok(()), _) => ()
+            };
 
             id
         };
 
-        let (uniforms, attributes, blocks, varyings) = {
-            unsafe {
-                (
-                    reflect_uniforms(&mut ctxt, id),
-                    reflect_attributes(&mut ctxt, id),
-                    reflect_uniform_blocks(&mut ctxt, id),
-                    reflect_transform_feedback(&mut ctxt, id),
-                )
+        let (uniforms, attributes, outputs, blocks, varyings) = unsafe {
+            match reflect_program(&mut ctxt, id, reflect, reflect_builtins) {
+                Ok(r) => r,
+                Err(err) => {
+                    delete_program_object(&mut ctxt, id);
+                    return Err(ProgramCreationError::ReflectionFailed(err));
+                }
             }
         };
 
+        // the values of the uniforms are only the ones of their initializers right after
+        // linking, so they are captured once here
+        let uniform_defaults = unsafe { reflect_uniform_defaults(&mut ctxt, id, &uniforms) };
+
+        register_live_program(facade.get_context(), id);
+
         Ok(Program {
             context: facade.get_context().clone(),
             id: id,
             uniforms: uniforms,
+            uniform_defaults: uniform_defaults,
             uniform_blocks: blocks,
             attributes: attributes,
+            outputs: outputs,
             frag_data_locations: RefCell::new(HashMap::with_hash_state(Default::default())),
             varyings: varyings,
             has_tessellation_shaders: has_tessellation_shaders,
+            source: None,
+            source_hash: None,
+            early_fragment_tests: false,
+            writes_point_size: false,
+            link_log: None,
+            context_info: ContextInfo::from_context(&ctxt),
+            reflect: reflect,
+            reflect_builtins: reflect_builtins,
         })
     }
 
     /// Creates a program from binary.
     ///
-    /// Must only be called if `input` is a `ProgramCreationInput::Binary`, will
-    /// panic otherwise.
-    fn from_binary_impl<F>(facade: &F, input: ProgramCreationInput)
+    /// Must only be called if `input` is a `ProgramCreationInput::Binary` or
+    /// `ProgramCreationInput::Binaries`, will panic otherwise.
+    fn from_binary_impl<F>(facade: &F, input: ProgramCreationInput, reflect: bool)
                            -> Result<Program, ProgramCreationError> where F: Facade
     {
-        let binary = match input {
-            ProgramCreationInput::Binary { data } => data,
+        let single;
+        let (binaries, reflect_builtins): (&[Binary], bool) = match input {
+            ProgramCreationInput::Binary { data, reflect_builtins } => {
+                single = [data];
+                (&single[..], reflect_builtins)
+            },
+            ProgramCreationInput::Binaries { data, reflect_builtins } => (data, reflect_builtins),
             _ => unreachable!()
         };
 
+        let mut errors = Vec::new();
+        for binary in binaries.iter() {
+            match Program::from_single_binary(facade, binary, reflect, reflect_builtins) {
+                Ok(program) => return Ok(program),
+                Err(err) => errors.push(err)
+            }
+        }
+
+        if errors.len() == 1 {
+            return Err(errors.pop().unwrap());
+        }
+
+        let log = if errors.is_empty() {
+            "No binary was provided".to_string()
+        } else {
+            errors.iter().enumerate().map(|(num, err)| format!("Binary #{}: {}", num, err))
+                  .collect::<Vec<_>>().connect("\n")
+        };
+
+        Err(ProgramCreationError::LinkingError(ShaderError {
+            stage: None,
+            log: log,
+        }))
+    }
+
+    /// Creates a program from a single binary.
+    ///
+    /// The program object is destroyed if the binary can't be loaded.
+    fn from_single_binary<F>(facade: &F, binary: &Binary, reflect: bool, reflect_builtins: bool)
+                             -> Result<Program, ProgramCreationError> where F: Facade
+    {
         let mut ctxt = facade.get_context().make_current();
 
         let id = unsafe {
-            let id = create_program(&mut ctxt);
+            let id = try!(create_program(&mut ctxt));
 
             match id {
                 Handle::Id(id) => {
@@ -353,33 +1449,338 @@ impl Program {
                 Handle::Handle(id) => unreachable!()
             };
 
-            // checking for errors
-            try!(check_program_link_errors(&mut ctxt, id));
+            // checking for errors, and destroying the program so that the next candidate
+            // starts from a new one
+            if let Err(err) = check_program_link_errors(&mut ctxt, id) {
+                match id {
+                    Handle::Id(id) => ctxt.gl.DeleteProgram(id),
+                    Handle::Handle(_) => unreachable!()
+                }
+
+                return Err(err);
+            }
 
             id
         };
 
-        let (uniforms, attributes, blocks, varyings) = unsafe {
-            (
-                reflect_uniforms(&mut ctxt, id),
-                reflect_attributes(&mut ctxt, id),
-                reflect_uniform_blocks(&mut ctxt, id),
-                reflect_transform_feedback(&mut ctxt, id),
-            )
+        let (uniforms, attributes, outputs, blocks, varyings) = unsafe {
+            match reflect_program(&mut ctxt, id, reflect, reflect_builtins) {
+                Ok(r) => r,
+                Err(err) => {
+                    delete_program_object(&mut ctxt, id);
+                    return Err(ProgramCreationError::ReflectionFailed(err));
+                }
+            }
         };
 
+        // the values of the uniforms are only the ones of their initializers right after
+        // linking, so they are captured once here
+        let uniform_defaults = unsafe { reflect_uniform_defaults(&mut ctxt, id, &uniforms) };
+
+        register_live_program(facade.get_context(), id);
+
         Ok(Program {
             context: facade.get_context().clone(),
             id: id,
             uniforms: uniforms,
+            uniform_defaults: uniform_defaults,
             uniform_blocks: blocks,
             attributes: attributes,
+            outputs: outputs,
             frag_data_locations: RefCell::new(HashMap::with_hash_state(Default::default())),
             varyings: varyings,
             has_tessellation_shaders: true,     // FIXME: 
+            source: None,
+            source_hash: None,
+            early_fragment_tests: false,
+            writes_point_size: false,
+            link_log: None,
+            context_info: ContextInfo::from_context(&ctxt),
+            reflect: reflect,
+            reflect_builtins: reflect_builtins,
         })
     }
 
+    /// Creates a program from SPIR-V modules.
+    ///
+    /// Must only be called if `input` is a `ProgramCreationInput::SpirV`, will
+    /// panic otherwise.
+    fn from_spirv_impl<F>(facade: &F, input: ProgramCreationInput, reflect: bool)
+                          -> Result<Program, ProgramCreationError> where F: Facade
+    {
+        let (stages, specialization_constants, retain_link_log, reflect_builtins) = match input {
+            ProgramCreationInput::SpirV { stages, specialization_constants, retain_link_log,
+                                          reflect_builtins } =>
+            {
+                (stages, specialization_constants, retain_link_log, reflect_builtins)
+            },
+            _ => unreachable!()
+        };
+
+        let shaders_store = {
+            let mut shaders_store = Vec::new();
+            for &(stage, binary, entry_point) in stages.iter() {
+                shaders_store.push(try!(build_spirv_shader(facade, stage.to_glenum(), binary,
+                                                           entry_point,
+                                                           specialization_constants)));
+            }
+            shaders_store
+        };
+
+        let shaders_refs = shaders_store.iter().collect::<Vec<_>>();
+        let mut program = try!(Program::link_shaders(facade, &shaders_refs, None, &[], reflect,
+                                                     reflect_builtins, None));
+
+        if retain_link_log {
+            program.store_link_log();
+        }
+
+        Ok(program)
+    }
+
+    /// Creates a program from vendor-specific shader binaries.
+    ///
+    /// Must only be called if `input` is a `ProgramCreationInput::ShaderBinaries`, will
+    /// panic otherwise.
+    fn from_shader_binaries_impl<F>(facade: &F, input: ProgramCreationInput, reflect: bool)
+                                    -> Result<Program, ProgramCreationError> where F: Facade
+    {
+        let (stages, retain_link_log, reflect_builtins) = match input {
+            ProgramCreationInput::ShaderBinaries { stages, retain_link_log, reflect_builtins } => {
+                (stages, retain_link_log, reflect_builtins)
+            },
+            _ => unreachable!()
+        };
+
+        let shaders_store = {
+            let mut shaders_store = Vec::new();
+            for &(stage, format, binary) in stages.iter() {
+                shaders_store.push(try!(build_binary_shader(facade, stage.to_glenum(), format,
+                                                            binary)));
+            }
+            shaders_store
+        };
+
+        let shaders_refs = shaders_store.iter().collect::<Vec<_>>();
+        let mut program = try!(Program::link_shaders(facade, &shaders_refs, None, &[], reflect,
+                                                     reflect_builtins, None));
+
+        if retain_link_log {
+            program.store_link_log();
+        }
+
+        Ok(program)
+    }
+
+    /// Builds a new program from the source code of this one, with additional `#define`
+    /// directives.
+    ///
+    /// Each `(name, value)` in `defines` is turned into a `#define name value` directive, which
+    /// is inserted after the `#version` directive of each shader. The line numbers in the
+    /// compilation errors are still the ones of the original source. This is useful to build
+    /// multiple variants of the same program, for example with and without shadows.
+    ///
+    /// The new program is independent from this one. It keeps the original source code, so
+    /// calling this function on it again doesn't accumulate the defines. The `strict` flag and
+    /// the other parameters of the creation are kept as well, except `source_transform`, which
+    /// can't be stored and is therefore not applied to the new program.
+    ///
+    /// Returns `SourceNotAvailable` if this program was not created from GLSL source code with
+    /// `retain_source: true`.
+    pub fn recompile_with_defines<F>(&self, facade: &F, defines: &[(&str, &str)])
+                                     -> Result<Program, ProgramCreationError> where F: Facade
+    {
+        let source = match self.source {
+            Some(ref s) => s,
+            None => return Err(ProgramCreationError::SourceNotAvailable)
+        };
+
+        let vertex_shader = inject_defines(&source.vertex_shader, defines);
+        let tessellation_control_shader = source.tessellation_control_shader.as_ref()
+                                                .map(|s| inject_defines(s, defines));
+        let tessellation_evaluation_shader = source.tessellation_evaluation_shader.as_ref()
+                                                   .map(|s| inject_defines(s, defines));
+        let geometry_shader = source.geometry_shader.as_ref().map(|s| inject_defines(s, defines));
+        let fragment_shader = inject_defines(&source.fragment_shader, defines);
+        let indexed_frag_data_locations = source.indexed_frag_data_locations.as_ref().map(|l| {
+            l.iter().map(|&(ref name, color, index)| (&name[..], color, index))
+             .collect::<Vec<_>>()
+        });
+
+        let mut program = try!(Program::from_source_impl(facade, ProgramCreationInput::SourceCode {
+            vertex_shader: &vertex_shader,
+            tessellation_control_shader: tessellation_control_shader.as_ref().map(|s| &s[..]),
+            tessellation_evaluation_shader: tessellation_evaluation_shader.as_ref()
+                                                                          .map(|s| &s[..]),
+            geometry_shader: geometry_shader.as_ref().map(|s| &s[..]),
+            fragment_shader: &fragment_shader,
+            transform_feedback_varyings: source.transform_feedback_varyings.clone(),
+            indexed_frag_data_locations: indexed_frag_data_locations.as_ref().map(|l| &l[..]),
+            source_transform: None,
+            retain_link_log: self.link_log.is_some(),
+            retain_source: true,
+            strict: source.strict,
+            missing_version: match source.default_version {
+                Some(ref v) => MissingVersion::Default(v),
+                None => MissingVersion::Ignore,
+            },
+            reflect_builtins: self.reflect_builtins,
+        }, self.reflect, None));
+
+        // the original source code is kept, so that the defines don't accumulate
+        program.source = Some(source.clone());
+        Ok(program)
+    }
+
+    /// Queries again the uniforms, attributes, outputs, uniform blocks and transform feedback
+    /// varyings of the program, and replaces the ones that were stored.
+    ///
+    /// The reflection data is normally queried only once when the program is created. You
+    /// only need to call this function if you modified the program with raw OpenGL calls.
+    /// Programs created with `new_without_reflection` stay without reflection data.
+    ///
+    /// Returns `ReflectionFailed` if the data can't be queried, in which case the previous
+    /// data is kept.
+    pub fn refresh_reflection(&mut self) -> Result<(), ProgramCreationError> {
+        let (uniforms, attributes, outputs, blocks, varyings) = {
+            let mut ctxt = self.context.make_current();
+            let result = unsafe {
+                reflect_program(&mut ctxt, self.id, self.reflect, self.reflect_builtins)
+            };
+            try!(result.map_err(ProgramCreationError::ReflectionFailed))
+        };
+
+        self.uniforms = uniforms;
+        self.attributes = attributes;
+        self.outputs = outputs;
+        self.uniform_blocks = blocks;
+        self.varyings = varyings;
+        self.frag_data_locations.borrow_mut().clear();
+        Ok(())
+    }
+
+    /// Returns the `Context` associated with this program.
+    pub fn get_context(&self) -> &Rc<Context> {
+        &self.context
+    }
+
+    /// Returns a hash of the source code that was compiled to create this program, or `None`
+    /// if the program was not created from source code.
+    ///
+    /// The hash is computed from the source code of all the stages as it was passed to the
+    /// compiler, which includes the defines added by `recompile_with_defines`, the `#version`
+    /// directive added because of `MissingVersion::Default`, the default precision added on
+    /// OpenGL ES and the result of `source_transform`. It doesn't change between executions,
+    /// which makes it suitable as a key for a cache of program binaries.
+    pub fn source_hash(&self) -> Option<u64> {
+        self.source_hash
+    }
+
+    /// Returns a rough estimate of the number of bytes of CPU memory used by this program.
+    ///
+    /// This includes the reflection data, and the source code and link log if they are
+    /// retained. The estimate is not exact, as it ignores the overhead of the allocator and
+    /// the internal layout of the hash maps, but it is representative enough to compare
+    /// programs with each other. The memory used by the driver is not included.
+    pub fn cpu_memory_estimate(&self) -> usize {
+        let mut total = mem::size_of::<Program>();
+
+        total += reflection_map_memory(&self.uniforms);
+        total += reflection_map_memory(&self.uniform_defaults);
+        total += reflection_map_memory(&self.attributes);
+        total += reflection_map_memory(&self.outputs);
+        total += reflection_map_memory(&*self.frag_data_locations.borrow());
+
+        total += reflection_map_memory(&self.uniform_blocks);
+        for block in self.uniform_blocks.values() {
+            total += block.members.capacity() * mem::size_of::<UniformBlockMember>();
+            total += block.members.iter().fold(0, |t, m| t + m.name.capacity());
+        }
+
+        if let Some((ref varyings, _)) = self.varyings {
+            total += varyings.capacity() * mem::size_of::<TransformFeedbackVarying>();
+            total += varyings.iter().fold(0, |t, v| t + v.name.capacity());
+        }
+
+        if let Some(ref source) = self.source {
+            total += source.vertex_shader.capacity() + source.fragment_shader.capacity();
+            total += [&source.tessellation_control_shader, &source.tessellation_evaluation_shader,
+                      &source.geometry_shader].iter()
+                        .fold(0, |t, s| t + s.as_ref().map(|s| s.capacity()).unwrap_or(0));
+
+            if let Some((ref names, _)) = source.transform_feedback_varyings {
+                total += names.capacity() * mem::size_of::<String>();
+                total += names.iter().fold(0, |t, n| t + n.capacity());
+            }
+
+            if let Some(ref locations) = source.indexed_frag_data_locations {
+                total += locations.capacity() * mem::size_of::<(String, u32, u32)>();
+                total += locations.iter().fold(0, |t, l| t + l.0.capacity());
+            }
+        }
+
+        if let Some(ref log) = self.link_log {
+            total += log.capacity();
+        }
+
+        total
+    }
+
+    /// Returns the OpenGL id of the program, or `None` if the program was created with
+    /// `GL_ARB_shader_objects` and is identified by a handle instead.
+    ///
+    /// This is the same as `get_id`, but avoids having to match on `Handle` when you only
+    /// target OpenGL 2.0 or above.
+    pub fn gl_id(&self) -> Option<u32> {
+        match self.id {
+            Handle::Id(id) => Some(id),
+            Handle::Handle(_) => None,
+        }
+    }
+
+    /// Returns a short string that identifies the program, like `id=42`, or `arb_handle=0x2a`
+    /// if the program was created with `GL_ARB_shader_objects`.
+    ///
+    /// This is meant to be used in logs.
+    pub fn handle_debug_string(&self) -> String {
+        match self.id {
+            Handle::Id(id) => format!("id={}", id),
+            Handle::Handle(handle) => format!("arb_handle={:#x}", handle as usize),
+        }
+    }
+
+    /// Returns a summary of the features of the context that were available when this
+    /// program was created.
+    ///
+    /// Including this in bug reports helps understanding why a program behaves differently
+    /// on different machines.
+    pub fn creation_context_info(&self) -> ContextInfo {
+        self.context_info
+    }
+
+    /// Returns true if a feature can be used with this program.
+    ///
+    /// The result is computed from the version and extensions of the context that created the
+    /// program, using the same checks as glium. Programs created with
+    /// `GL_ARB_shader_objects` on old contexts can't use transform feedback, binaries or
+    /// separable linkage.
+    pub fn supports_feature(&self, feature: ProgramFeature) -> bool {
+        let info = &self.context_info;
+        let is_id = match self.id { Handle::Id(_) => true, Handle::Handle(_) => false };
+
+        match feature {
+            ProgramFeature::BinaryRetrieval => is_id && info.program_binary,
+            ProgramFeature::TransformFeedback => is_id && info.transform_feedback,
+            ProgramFeature::Compute => info.compute_shader,
+            ProgramFeature::Geometry => info.geometry_shader,
+            ProgramFeature::Tessellation => info.tessellation_shader,
+            ProgramFeature::SeparableLinkage => is_id && info.separate_shader_objects,
+            ProgramFeature::UniformBlocks => info.uniform_buffer_object,
+            ProgramFeature::InterfaceQuery => is_id && info.program_interface_query,
+            ProgramFeature::SpirV => info.spirv,
+        }
+    }
+
     /// Returns the program's compiled binary.
     ///
     /// You can store the result in a file, then reload it later. This avoids having to compile
@@ -396,32 +1797,49 @@ impl Program {
     /// Returns the program's compiled binary.
     ///
     /// Same as `get_binary` but always available. Returns `None` if the backend doesn't support
-    /// getting or reloading the program's binary.
+    /// getting or reloading the program's binary, or if the driver fails to return it.
     pub fn get_binary_if_supported(&self) -> Option<Binary> {
         unsafe {
             let ctxt = self.context.make_current();
 
-            if ctxt.version >= &Version(Api::Gl, 4, 1) ||
-               ctxt.extensions.gl_arb_get_programy_binary
-            {
+            if self.supports_feature(ProgramFeature::BinaryRetrieval) {
                 let id = match self.id {
                     Handle::Id(id) => id,
                     Handle::Handle(_) => unreachable!()
                 };
 
-                let mut buf_len = mem::uninitialized();
-                ctxt.gl.GetProgramiv(id, gl::PROGRAM_BINARY_LENGTH, &mut buf_len);
+                // some drivers report a length that is different from the one of the binary
+                // they write, so the length is queried again and the whole query retried once
+                // if the binary doesn't fit
+                for _ in (0 .. 2) {
+                    // initialized in case the driver doesn't write them, for example on error
+                    let mut buf_len: gl::types::GLint = 0;
+                    ctxt.gl.GetProgramiv(id, gl::PROGRAM_BINARY_LENGTH, &mut buf_len);
 
-                let mut format = mem::uninitialized();
-                let mut storage: Vec<u8> = Vec::with_capacity(buf_len as usize);
-                ctxt.gl.GetProgramBinary(id, buf_len, &mut buf_len, &mut format,
-                                         storage.as_mut_ptr() as *mut libc::c_void);
-                storage.set_len(buf_len as usize);
+                    if buf_len <= 0 {
+                        return None;
+                    }
 
-                Some(Binary {
-                    format: format,
-                    content: storage,
-                })
+                    let mut written: gl::types::GLint = 0;
+                    let mut format: gl::types::GLenum = 0;
+                    let mut storage: Vec<u8> = Vec::with_capacity(buf_len as usize);
+                    ctxt.gl.GetProgramBinary(id, buf_len, &mut written, &mut format,
+                                             storage.as_mut_ptr() as *mut libc::c_void);
+
+                    // if the buffer is too small, the driver is supposed to write nothing
+                    if written <= 0 || written as usize > storage.capacity() {
+                        continue;
+                    }
+
+                    storage.set_len(written as usize);
+
+                    return Some(Binary {
+                        format: format,
+                        content: storage,
+                    });
+                }
+
+                None
 
             } else {
                 None
@@ -429,90 +1847,1211 @@ impl Program {
         }
     }
 
-    /// Returns the *location* of an output fragment, if it exists.
+    /// Builds a copy of this program on another facade, by retrieving its binary and loading
+    /// it.
     ///
-    /// The *location* is low-level information that is used internally by glium.
-    /// You probably don't need to call this function.
-    ///
-    /// You can declare output fragments in your shaders by writing:
-    ///
-    /// ```notrust
-    /// out vec4 foo;
-    /// ```
+    /// This is useful if you have multiple contexts, for example one per window. The format of
+    /// the binary is checked against the formats supported by `facade` before loading it. The
+    /// new program has the same reflection settings as this one.
     ///
-    pub fn get_frag_data_location(&self, name: &str) -> Option<u32> {
-        // looking for a cached value
-        if let Some(result) = self.frag_data_locations.borrow_mut().get(name) {
-            return result.clone();
-        }
-
-        // querying opengl
-        let name_c = ffi::CString::new(name.as_bytes()).unwrap();
-
-        let ctxt = self.context.make_current();
-
-        let value = unsafe {
-            match self.id {
-                Handle::Id(id) => {
-                    assert!(ctxt.version >= &Version(Api::Gl, 2, 0));
-                    ctxt.gl.GetFragDataLocation(id, name_c.as_bytes_with_nul().as_ptr()
-                                                as *const libc::c_char)
-                },
-                Handle::Handle(id) => {
-                    // not supported
-                    -1
-                }
-            }
+    /// Returns `None` if the binary of this program can't be retrieved, or if it can't be
+    /// loaded by `facade`.
+    pub fn clone_binary_to<F>(&self, facade: &F) -> Option<Program> where F: Facade {
+        let binary = match self.get_binary_if_supported() {
+            Some(b) => b,
+            None => return None
         };
 
-        let location = match value {
-            -1 => None,
-            a => Some(a as u32),
-        };
+        if !binary.is_format_supported(facade) {
+            return None;
+        }
 
-        self.frag_data_locations.borrow_mut().insert(name.to_string(), location);
-        location
+        Program::new_impl(facade, ProgramCreationInput::Binary {
+            data: binary,
+            reflect_builtins: self.reflect_builtins,
+        }, self.reflect).ok()
     }
 
-    /// Returns informations about a uniform variable, if it exists.
-    pub fn get_uniform(&self, name: &str) -> Option<&Uniform> {
+    /// Destroys the vertex array objects that glium has cached for this program.
+    ///
+    /// A VAO stores the layout of the attributes at the time it was created. If you modify the
+    /// attribute bindings of the program with raw OpenGL calls, call this function so that
+    /// new VAOs matching the new layout are built the next time you draw.
+    pub fn invalidate_vaos(&self) {
+        let mut ctxt = self.context.make_current();
+        self.context.vertex_array_objects.purge_program(&mut ctxt, self.id);
+    }
+
+    /// Forces the driver to finish compiling the program.
+    ///
+    /// Many drivers delay a part of the compilation until the first time a program is used,
+    /// which causes a hitch during the first draw. This function binds the program and issues
+    /// a draw command with zero vertices, which is enough for most drivers to finalize it.
+    /// Calling it while loading moves this cost out of the rendering loop.
+    ///
+    /// Since nothing is drawn, this has no visible effect.
+    ///
+    /// The draw command targets the default framebuffer instead of a scratch framebuffer,
+    /// because some drivers compile a variant of the program for each framebuffer format and
+    /// the default framebuffer is the most likely target. If it is not complete, for example
+    /// with a context that has no window, only the program is bound.
+    ///
+    /// Returns `true` if the draw command was issued, and `false` if only the program was
+    /// bound, in which case the driver may still finish compiling it during the first draw.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `facade` is not the one that the program was created with.
+    pub fn warm_up<F>(&self, facade: &F) -> bool where F: Facade {
+        assert!(&**facade.get_context() as *const Context == &*self.context as *const _,
+                "The program must be warmed up with the facade it was created with");
+
+        let mut ctxt = self.context.make_current();
+
+        unsafe {
+            if ctxt.state.program != self.id {
+                match self.id {
+                    Handle::Id(id) => ctxt.gl.UseProgram(id),
+                    Handle::Handle(id) => ctxt.gl.UseProgramObjectARB(id),
+                }
+                ctxt.state.program = self.id;
+            }
+
+            // the primitive type must match the input of the tessellation or geometry shader,
+            // otherwise the draw command is rejected
+            let primitive = if self.has_tessellation_shaders {
+                gl::PATCHES
+            } else {
+                geometry_input_type(&mut ctxt, self.id).unwrap_or(gl::POINTS)
+            };
+
+            fbo::bind_framebuffer(&mut ctxt, 0, true, false);
+
+            // drawing to an incomplete framebuffer generates an error
+            let status = if ctxt.version >= &Version(Api::Gl, 3, 0) {
+                ctxt.gl.CheckFramebufferStatus(gl::DRAW_FRAMEBUFFER)
+            } else if ctxt.version >= &Version(Api::GlEs, 2, 0) {
+                ctxt.gl.CheckFramebufferStatus(gl::FRAMEBUFFER)
+            } else if ctxt.extensions.gl_ext_framebuffer_object {
+                ctxt.gl.CheckFramebufferStatusEXT(gl::FRAMEBUFFER_EXT)
+            } else {
+                gl::FRAMEBUFFER_COMPLETE
+            };
+
+            if status != gl::FRAMEBUFFER_COMPLETE {
+                return false;
+            }
+
+            // core contexts can't draw without a vertex array object, so we use a temporary one
+            let vao = if ctxt.version >= &Version(Api::Gl, 3, 0) ||
+                         ctxt.version >= &Version(Api::GlEs, 3, 0) ||
+                         ctxt.extensions.gl_arb_vertex_array_object
+            {
+                let mut vao = 0;
+                ctxt.gl.GenVertexArrays(1, &mut vao);
+                ctxt.gl.BindVertexArray(vao);
+                Some(vao)
+            } else {
+                None
+            };
+
+            ctxt.gl.DrawArrays(primitive, 0, 0);
+
+            if let Some(vao) = vao {
+                // deleting the bound vertex array object reverts the binding to zero
+                ctxt.gl.DeleteVertexArrays(1, &vao);
+                ctxt.state.vertex_array = 0;
+            }
+        }
+
+        true
+    }
+
+    /// Asks the driver to keep the binary of the program available, then links the program
+    /// again.
+    ///
+    /// The `GL_PROGRAM_BINARY_RETRIEVABLE_HINT` is only taken into account when linking, which
+    /// is why relinking is necessary. This is useful if you decide after the creation of a
+    /// program that you want to store its binary.
+    ///
+    /// Linking again resets the values of the uniforms and may change the locations of the
+    /// uniforms and attributes, which is why this function takes `&mut self`: the reflection
+    /// data and the default values of the uniforms are queried again and replaced. If they can't
+    /// be queried, `ReflectionFailed` is returned and the previous data is kept.
+    ///
+    /// Returns `SourceNotAvailable` if the program was not created from source code, and
+    /// `BinaryNotSupported` if the backend doesn't support program binaries.
+    pub fn request_binary_retention(&mut self) -> Result<(), ProgramCreationError> {
+        if self.source_hash.is_none() {
+            return Err(ProgramCreationError::SourceNotAvailable);
+        }
+
+        let (uniforms, uniform_defaults, attributes, outputs, blocks, varyings) = {
+            let report_debug_output = self.context.get_report_debug_output_during_linking();
+            let mut ctxt = self.context.make_current();
+
+            if !(ctxt.version >= &Version(Api::Gl, 4, 1)) &&
+               !ctxt.extensions.gl_arb_get_programy_binary
+            {
+                return Err(ProgramCreationError::BinaryNotSupported);
+            }
+
+            let id = match self.id {
+                Handle::Id(id) => id,
+                Handle::Handle(_) => unreachable!()
+            };
+
+            unsafe {
+                ctxt.gl.ProgramParameteri(id, gl::PROGRAM_BINARY_RETRIEVABLE_HINT,
+                                          gl::TRUE as gl::types::GLint);
+
+                {
+                    let _lock = COMPILER_GLOBAL_LOCK.lock();
+
+                    ctxt.report_debug_output_errors.set(report_debug_output);
+                    ctxt.gl.LinkProgram(id);
+                    ctxt.report_debug_output_errors.set(true);
+                }
+
+                try!(check_program_link_errors(&mut ctxt, self.id));
+            }
+
+            // the VAOs of this program were built with the old attribute locations
+            self.context.vertex_array_objects.purge_program(&mut ctxt, self.id);
+            self.frag_data_locations.borrow_mut().clear();
+
+            // the locations may have changed ; nothing is replaced until everything has been
+            // queried successfully
+            let (uniforms, attributes, outputs, blocks, varyings) = unsafe {
+                try!(reflect_program(&mut ctxt, self.id, self.reflect, self.reflect_builtins)
+                        .map_err(ProgramCreationError::ReflectionFailed))
+            };
+
+            let uniform_defaults = unsafe {
+                reflect_uniform_defaults(&mut ctxt, self.id, &uniforms)
+            };
+
+            (uniforms, uniform_defaults, attributes, outputs, blocks, varyings)
+        };
+
+        self.uniforms = uniforms;
+        self.uniform_defaults = uniform_defaults;
+        self.attributes = attributes;
+        self.outputs = outputs;
+        self.uniform_blocks = blocks;
+        self.varyings = varyings;
+
+        if self.link_log.is_some() {
+            self.store_link_log();
+        }
+
+        Ok(())
+    }
+
+    /// Returns the info log of the linker, if it was retained when the program was created.
+    ///
+    /// The log is only retained if you passed `retain_link_log: true` when creating the program.
+    /// Unlike the logs of the individual shaders, this is the log of the whole program, which
+    /// may contain warnings even if the linking was successful. Its content is
+    /// implementation-defined.
+    pub fn link_log(&self) -> Option<&str> {
+        self.link_log.as_ref().map(|l| &l[..])
+    }
+
+    /// Queries the info log of the program and stores it in `link_log`.
+    fn store_link_log(&mut self) {
+        let log = {
+            let mut ctxt = self.context.make_current();
+            unsafe { get_program_info_log(&mut ctxt, self.id) }
+        };
+
+        self.link_log = Some(log);
+    }
+
+    /// Returns the assembly code generated by the driver for this program, if available.
+    ///
+    /// This is a best-effort function that is mostly useful for debugging performances. For
+    /// the moment only nVidia drivers are supported: their program binaries embed the text of
+    /// the compiled assembly programs, which is extracted here.
+    ///
+    /// Returns `None` if the driver is not supported or if the backend doesn't support
+    /// getting the program's binary.
+    pub fn get_disassembly(&self) -> Option<String> {
+        self.get_binary_if_supported().and_then(|binary| extract_nv_assembly(&binary.content))
+    }
+
+    /// Returns the *location* of an output fragment, if it exists.
+    ///
+    /// The *location* is low-level information that is used internally by glium.
+    /// You probably don't need to call this function.
+    ///
+    /// You can declare output fragments in your shaders by writing:
+    ///
+    /// ```notrust
+    /// out vec4 foo;
+    /// ```
+    ///
+    pub fn get_frag_data_location(&self, name: &str) -> Option<u32> {
+        // looking for a cached value
+        if let Some(result) = self.frag_data_locations.borrow_mut().get(name) {
+            return result.clone();
+        }
+
+        // querying opengl
+        let name_c = ffi::CString::new(name.as_bytes()).unwrap();
+
+        let ctxt = self.context.make_current();
+
+        let value = unsafe {
+            match self.id {
+                Handle::Id(id) => {
+                    assert!(ctxt.version >= &Version(Api::Gl, 2, 0));
+                    ctxt.gl.GetFragDataLocation(id, name_c.as_bytes_with_nul().as_ptr()
+                                                as *const libc::c_char)
+                },
+                Handle::Handle(id) => {
+                    // not supported
+                    -1
+                }
+            }
+        };
+
+        let location = match value {
+            -1 => None,
+            a => Some(a as u32),
+        };
+
+        self.frag_data_locations.borrow_mut().insert(name.to_string(), location);
+        location
+    }
+
+    /// Returns the index of an output fragment, if it exists.
+    ///
+    /// The index is the source of dual-source blending that the output is bound to. See the
+    /// `indexed_frag_data_locations` field of `ProgramCreationInput::SourceCode`.
+    ///
+    /// Always returns `None` if the backend doesn't support dual-source blending.
+    pub fn get_frag_data_index(&self, name: &str) -> Option<u32> {
+        let ctxt = self.context.make_current();
+
+        let id = match self.id {
+            Handle::Id(id) if ctxt.version >= &Version(Api::Gl, 3, 3) => id,
+            _ => return None
+        };
+
+        let name_c = ffi::CString::new(name.as_bytes()).unwrap();
+
+        match unsafe { ctxt.gl.GetFragDataIndex(id, name_c.as_ptr()) } {
+            -1 => None,
+            a => Some(a as u32),
+        }
+    }
+
+    /// Returns true if the fragment shader contains a `layout(early_fragment_tests) in;`
+    /// declaration.
+    ///
+    /// With this declaration, the depth and stencil tests are performed before the fragment
+    /// shader is executed. OpenGL doesn't provide a way to query this, so this is detected by
+    /// parsing the source code of the fragment shader. Returns `false` if the program was not
+    /// created from source code.
+    pub fn forces_early_fragment_tests(&self) -> bool {
+        self.early_fragment_tests
+    }
+
+    /// Returns true if the last stage before rasterization writes `gl_PointSize`.
+    ///
+    /// Points drawn with such a program only have the size written by the shader if
+    /// `GL_PROGRAM_POINT_SIZE` is enabled, otherwise the point size of the draw parameters is
+    /// used. The stage that matters is the geometry shader if there is one, then the
+    /// tessellation evaluation shader, then the vertex shader.
+    ///
+    /// This is a heuristic based on the source code, which looks for an assignment to
+    /// `gl_PointSize`. It doesn't know about comments or preprocessor conditions. Returns
+    /// `false` if the program was not created from source code.
+    pub fn writes_point_size(&self) -> bool {
+        self.writes_point_size
+    }
+
+    /// Returns the value of a uniform right after the program was linked, which is the value
+    /// of its initializer in the source code, or zero if it has none.
+    ///
+    /// This can be used to show the default values of a shader in an editor. Only uniforms
+    /// that are not arrays and whose type is a float, a vector or matrix of floats, or an
+    /// integer are supported. Returns `None` for other uniforms and for programs created
+    /// without reflection.
+    ///
+    /// The values are captured when the program is linked, which means when it is created and
+    /// by `request_binary_retention`. They are not modified by `set_uniform` or
+    /// `refresh_reflection`.
+    pub fn get_uniform_default(&self, name: &str) -> Option<UniformValue<'static>> {
+        self.uniform_defaults.get(name).map(|value| *value)
+    }
+
+    /// Returns informations about a uniform variable, if it exists.
+    ///
+    /// All the active uniforms are queried when the program is created, so this function is
+    /// a simple hash map lookup and never calls OpenGL, whether the uniform exists or not.
+    /// This is unlike `get_frag_data_location`, which queries OpenGL the first time a name is
+    /// requested and caches the result, including misses.
+    pub fn get_uniform(&self, name: &str) -> Option<&Uniform> {
         self.uniforms.get(name)
     }
-    
-    /// Returns an iterator to the list of uniforms.
-    pub fn uniforms(&self) -> hash_map::Iter<String, Uniform> {
-        self.uniforms.iter()
+
+    /// Returns informations about a uniform variable, ignoring the case of its name.
+    ///
+    /// An exact match is returned first if there is one. Otherwise, this function iterates
+    /// over all the uniforms of the program, which makes it O(n) in the number of uniforms.
+    /// If several uniforms only differ by their case, which one is returned is unspecified.
+    ///
+    /// This is meant to be used by tools and editors, you should use `get_uniform` everywhere
+    /// else.
+    pub fn get_uniform_ci(&self, name: &str) -> Option<&Uniform> {
+        if let Some(uniform) = self.uniforms.get(name) {
+            return Some(uniform);
+        }
+
+        self.uniforms.iter().find(|&(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, u)| u)
+    }
+    
+    /// Sets the value of a uniform of the program.
+    ///
+    /// The value stays in the program until it is overwritten, for example by drawing with a
+    /// uniform of the same name. This is useful to initialize uniforms that never change.
+    ///
+    /// If `glProgramUniform*` is supported (OpenGL 4.1, OpenGL ES 3.1,
+    /// `GL_ARB_separate_shader_objects` or `GL_EXT_separate_shader_objects` on OpenGL ES),
+    /// the value is set without binding the program. Otherwise the program is bound first.
+    ///
+    /// Textures and uniform buffers can only be bound when drawing, and will return an error.
+    pub fn set_uniform<'a, V>(&self, name: &str, value: V) -> Result<(), UniformTypeMismatch>
+                              where V: IntoUniformValue<'a>
+    {
+        self.set_uniform_value(name, value.into_uniform_value())
+    }
+
+    /// Implementation of `set_uniform`.
+    fn set_uniform_value(&self, name: &str, value: UniformValue)
+                         -> Result<(), UniformTypeMismatch>
+    {
+        let uniform = match self.uniforms.get(name) {
+            Some(u) => u,
+            None => return Err(UniformTypeMismatch {
+                name: name.to_string(),
+                expected: None,
+                obtained: value.get_type(),
+            })
+        };
+
+        if !value.is_usable_with(&uniform.ty) {
+            return Err(UniformTypeMismatch {
+                name: name.to_string(),
+                expected: Some(uniform.ty),
+                obtained: value.get_type(),
+            });
+        }
+
+        let location = uniform.location;
+        let program = self.id;
+
+        let mut ctxt = self.context.make_current();
+
+        // on OpenGL ES, `GL_EXT_separate_shader_objects` provides the same functions with an
+        // `EXT` suffix
+        let (use_dsa, use_dsa_ext) = match program {
+            Handle::Id(_) => {
+                let core = ctxt.version >= &Version(Api::Gl, 4, 1) ||
+                           ctxt.version >= &Version(Api::GlEs, 3, 1) ||
+                           ctxt.extensions.gl_arb_separate_shader_objects;
+                let ext = !core && ctxt.version.0 == Api::GlEs &&
+                          ctxt.extensions.gl_ext_separate_shader_objects;
+                (core, ext)
+            },
+            Handle::Handle(_) => (false, false),
+        };
+
+        // binding the program if necessary
+        if !use_dsa && !use_dsa_ext && ctxt.state.program != program {
+            unsafe {
+                match program {
+                    Handle::Id(id) => ctxt.gl.UseProgram(id),
+                    Handle::Handle(id) => ctxt.gl.UseProgramObjectARB(id),
+                }
+            }
+
+            ctxt.state.program = program;
+        }
+
+        macro_rules! uniform(
+            ($program_uniform:ident, $program_uniform_ext:ident, $uniform:ident,
+             $uniform_arb:ident, $($params:expr),+) => (
+                unsafe {
+                    match program {
+                        Handle::Id(id) if use_dsa => ctxt.gl.$program_uniform(id, location,
+                                                                               $($params),+),
+                        Handle::Id(id) if use_dsa_ext => {
+                            ctxt.gl.$program_uniform_ext(id, location, $($params),+)
+                        },
+                        Handle::Id(_) => ctxt.gl.$uniform(location, $($params),+),
+                        Handle::Handle(_) => ctxt.gl.$uniform_arb(location, $($params),+),
+                    }
+                }
+            )
+        );
+
+        match value {
+            UniformValue::SignedInt(val) => {
+                uniform!(ProgramUniform1i, ProgramUniform1iEXT, Uniform1i, Uniform1iARB, val);
+            },
+            UniformValue::UnsignedInt(val) => {
+                // Uniform1uiARB doesn't exist, and `Uniform1ui` requires OpenGL 3.0 or
+                // OpenGL ES 3.0 ; `GL_EXT_gpu_shader4` provides it with an `EXT` suffix
+                let core = ctxt.version >= &Version(Api::Gl, 3, 0) ||
+                           ctxt.version >= &Version(Api::GlEs, 3, 0);
+
+                unsafe {
+                    match program {
+                        Handle::Id(id) if use_dsa => ctxt.gl.ProgramUniform1ui(id, location, val),
+                        Handle::Id(id) if use_dsa_ext => {
+                            ctxt.gl.ProgramUniform1uiEXT(id, location, val)
+                        },
+                        Handle::Id(_) if core => ctxt.gl.Uniform1ui(location, val),
+                        Handle::Id(_) if ctxt.extensions.gl_ext_gpu_shader4 => {
+                            ctxt.gl.Uniform1uiEXT(location, val)
+                        },
+                        // without unsigned integers in GLSL, the uniform is a signed integer
+                        Handle::Id(_) => ctxt.gl.Uniform1i(location, val as gl::types::GLint),
+                        Handle::Handle(_) => ctxt.gl.Uniform1iARB(location,
+                                                                  val as gl::types::GLint),
+                    }
+                }
+            },
+            UniformValue::Float(val) => {
+                uniform!(ProgramUniform1f, ProgramUniform1fEXT, Uniform1f, Uniform1fARB, val);
+            },
+            UniformValue::Mat2(val) => {
+                uniform!(ProgramUniformMatrix2fv, ProgramUniformMatrix2fvEXT,
+                         UniformMatrix2fv, UniformMatrix2fvARB,
+                         1, gl::FALSE, val.as_ptr() as *const f32);
+            },
+            UniformValue::Mat3(val) => {
+                uniform!(ProgramUniformMatrix3fv, ProgramUniformMatrix3fvEXT,
+                         UniformMatrix3fv, UniformMatrix3fvARB,
+                         1, gl::FALSE, val.as_ptr() as *const f32);
+            },
+            UniformValue::Mat4(val) => {
+                uniform!(ProgramUniformMatrix4fv, ProgramUniformMatrix4fvEXT,
+                         UniformMatrix4fv, UniformMatrix4fvARB,
+                         1, gl::FALSE, val.as_ptr() as *const f32);
+            },
+            UniformValue::Vec2(val) => {
+                uniform!(ProgramUniform2fv, ProgramUniform2fvEXT, Uniform2fv, Uniform2fvARB,
+                         1, val.as_ptr() as *const f32);
+            },
+            UniformValue::Vec3(val) => {
+                uniform!(ProgramUniform3fv, ProgramUniform3fvEXT, Uniform3fv, Uniform3fvARB,
+                         1, val.as_ptr() as *const f32);
+            },
+            UniformValue::Vec4(val) => {
+                uniform!(ProgramUniform4fv, ProgramUniform4fvEXT, Uniform4fv, Uniform4fvARB,
+                         1, val.as_ptr() as *const f32);
+            },
+            _ => {
+                return Err(UniformTypeMismatch {
+                    name: name.to_string(),
+                    expected: Some(uniform.ty),
+                    obtained: None,
+                });
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Same as `set_uniform`, but requires the type of the value to be exactly the type of the
+    /// uniform.
+    ///
+    /// OpenGL silently ignores a value whose type doesn't match the uniform, for example a
+    /// `vec3` uploaded into a `vec4`. This function checks the type against the reflection
+    /// data before calling OpenGL, so that the mistake is caught.
+    pub fn set_uniform_checked<'a, V>(&self, name: &str, value: V)
+                                      -> Result<(), UniformTypeMismatch>
+                                      where V: IntoUniformValue<'a>
+    {
+        let value = value.into_uniform_value();
+
+        let error = UniformTypeMismatch {
+            name: name.to_string(),
+            expected: self.uniforms.get(name).map(|u| u.ty),
+            obtained: value.get_type(),
+        };
+
+        match (error.expected, error.obtained) {
+            (Some(expected), Some(obtained)) if expected == obtained => (),
+            _ => return Err(error)
+        };
+
+        self.set_uniform_value(name, value)
+    }
+
+    /// Returns an iterator to the list of uniforms.
+    ///
+    /// Built-in uniforms, whose name starts with `gl_`, are not part of this list unless
+    /// `reflect_builtins` was set when creating the program.
+    pub fn uniforms(&self) -> hash_map::Iter<String, Uniform> {
+        self.uniforms.iter()
+    }
+
+    /// Returns an iterator to the list of uniforms for which `predicate` returns `true`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let program: glium::Program = unsafe { std::mem::uninitialized() };
+    /// for (name, uniform) in program.uniforms_matching(|name, _| name.starts_with("material_")) {
+    ///     println!("{}: {:?}", name, uniform.ty);
+    /// }
+    /// ```
+    pub fn uniforms_matching<F>(&self, predicate: F) -> UniformsMatchingIter<F>
+                                where F: Fn(&str, &Uniform) -> bool
+    {
+        UniformsMatchingIter {
+            iter: self.uniforms.iter(),
+            predicate: predicate,
+        }
+    }
+
+    /// Returns the number of uniforms, ie. the number of elements returned by `uniforms()`.
+    pub fn uniform_count(&self) -> usize {
+        self.uniforms.len()
+    }
+    
+    /// Returns a list of uniform blocks.
+    #[deprecated = "Use `uniform_blocks` or `get_uniform_block` instead"]
+    // the exact type of the map is only kept here for backward compatibility
+    pub fn get_uniform_blocks(&self) -> &HashMap<String, UniformBlock, DefaultState<FnvHasher>> {
+        &self.uniform_blocks
+    }
+
+    /// Returns an iterator to the list of image uniforms.
+    ///
+    /// Image uniforms, like `uniform image2D img;`, are also part of `uniforms()`, but they
+    /// must be bound to image units with `glBindImageTexture` instead of texture units.
+    pub fn image_uniforms(&self) -> ImageUniformsIter {
+        ImageUniformsIter {
+            iter: self.uniforms.iter(),
+        }
+    }
+
+    /// Returns an iterator to the sampler uniforms of the program, with the kind of texture
+    /// they read from and their location.
+    ///
+    /// This can be used to know which textures must be bound before drawing. Sampler uniforms
+    /// are also part of `uniforms()`.
+    pub fn samplers(&self) -> SamplersIter {
+        SamplersIter {
+            iter: self.uniforms.iter(),
+        }
+    }
+
+    /// Passes all the reflection data of the program to a visitor.
+    ///
+    /// The uniforms are visited first, then the attributes, then the uniform blocks each
+    /// followed by their members, and finally the transform feedback varyings. The order of
+    /// the uniforms, attributes and blocks is unspecified.
+    pub fn accept_reflection_visitor<V>(&self, visitor: &mut V)
+                                        where V: ProgramReflectionVisitor
+    {
+        for (name, uniform) in self.uniforms.iter() {
+            visitor.visit_uniform(name, uniform);
+        }
+
+        for (name, attribute) in self.attributes.iter() {
+            visitor.visit_attribute(name, attribute);
+        }
+
+        for (name, block) in self.uniform_blocks.iter() {
+            visitor.visit_block(name, block);
+
+            for member in block.members.iter() {
+                visitor.visit_block_member(name, member);
+            }
+        }
+
+        for varying in self.get_transform_feedback_varyings().iter() {
+            visitor.visit_varying(varying);
+        }
+    }
+
+    /// Returns informations about a uniform block, if it exists.
+    pub fn get_uniform_block(&self, name: &str) -> Option<&UniformBlock> {
+        self.uniform_blocks.get(name)
+    }
+
+    /// Returns the size in bytes of the data of a uniform block, if the block exists.
+    ///
+    /// This is the minimal size of a buffer that is bound to this block.
+    pub fn get_uniform_block_size(&self, name: &str) -> Option<usize> {
+        self.uniform_blocks.get(name).map(|block| block.size)
+    }
+
+    /// Returns an iterator to the members of a uniform block, if the block exists.
+    ///
+    /// The offsets and strides of the members can be used to write the content of a buffer
+    /// that is bound to this block.
+    pub fn uniform_block_members(&self, block_name: &str)
+                                 -> Option<slice::Iter<UniformBlockMember>>
+    {
+        self.uniform_blocks.get(block_name).map(|block| block.members.iter())
+    }
+
+    /// Returns the number of active members of a uniform block, if the block exists.
+    ///
+    /// This is the same as the length of `uniform_block_members`, and doesn't call OpenGL.
+    pub fn num_uniform_block_members(&self, block_name: &str) -> Option<usize> {
+        self.uniform_blocks.get(block_name).map(|block| block.members.len())
+    }
+
+    /// Returns the stages of the program that reference a uniform block, if the block exists.
+    ///
+    /// A buffer only needs to be bound to the block if one of these stages is executed.
+    pub fn uniform_block_stages(&self, block_name: &str) -> Option<StageMask> {
+        self.uniform_blocks.get(block_name).map(|block| block.stages)
+    }
+
+    /// Returns an iterator to the list of uniform blocks.
+    pub fn uniform_blocks(&self) -> UniformBlocksIter {
+        UniformBlocksIter {
+            iter: self.uniform_blocks.iter(),
+        }
+    }
+
+    /// Returns the number of uniform blocks.
+    pub fn uniform_block_count(&self) -> usize {
+        self.uniform_blocks.len()
+    }
+
+    /// Returns the indices of a list of uniforms, or `None` for uniforms that don't exist.
+    ///
+    /// The *index* of a uniform is low-level information that can be used to query
+    /// informations about uniforms inside blocks. All the names are looked up with a
+    /// single call to `glGetUniformIndices`.
+    ///
+    /// Always returns `None`s if the backend doesn't support uniform blocks.
+    pub fn get_uniform_indices(&self, names: &[&str]) -> Vec<Option<u32>> {
+        let ctxt = self.context.make_current();
+
+        let id = match self.id {
+            Handle::Id(id) if ctxt.version >= &Version(Api::Gl, 3, 1) ||
+                              ctxt.extensions.gl_arb_uniform_buffer_object => id,
+            _ => return names.iter().map(|_| None).collect()
+        };
+
+        let names_c = names.iter().map(|name| {
+            ffi::CString::new(name.as_bytes()).unwrap()
+        }).collect::<Vec<_>>();
+        let names_ptr = names_c.iter().map(|n| n.as_ptr()).collect::<Vec<_>>();
+
+        let mut indices = ::std::iter::repeat(0).take(names.len())
+                                                .collect::<Vec<gl::types::GLuint>>();
+
+        unsafe {
+            ctxt.gl.GetUniformIndices(id, names_ptr.len() as gl::types::GLsizei,
+                                      names_ptr.as_ptr(), indices.as_mut_ptr());
+        }
+
+        indices.into_iter().map(|index| {
+            if index == gl::INVALID_INDEX { None } else { Some(index) }
+        }).collect()
+    }
+
+    /// Changes the binding point of a uniform block.
+    ///
+    /// Returns an error if the block doesn't exist.
+    pub fn set_uniform_block_binding(&self, name: &str, binding: u32) -> Result<(), String> {
+        self.set_uniform_block_bindings(&[(name, binding)])
+    }
+
+    /// Changes the binding points of multiple uniform blocks at once.
+    ///
+    /// All the names are checked before anything is modified. If one of the blocks doesn't
+    /// exist, an error containing its name is returned and no binding is changed.
+    pub fn set_uniform_block_bindings(&self, bindings: &[(&str, u32)]) -> Result<(), String> {
+        for &(name, _) in bindings.iter() {
+            if !self.uniform_blocks.contains_key(name) {
+                return Err(format!("Uniform block `{}` doesn't exist", name));
+            }
+        }
+
+        // nothing to do
+        if bindings.len() == 0 {
+            return Ok(());
+        }
+
+        let ctxt = self.context.make_current();
+
+        let id = match self.id {
+            Handle::Id(id) => id,
+            Handle::Handle(_) => unreachable!()     // uniform blocks are not reflected
+                                                    // with handles
+        };
+
+        // the names of the reflected blocks should always be valid, but passing
+        // `GL_INVALID_INDEX` to `glUniformBlockBinding` must be avoided at all costs
+        let mut indices = Vec::with_capacity(bindings.len());
+        for &(name, binding) in bindings.iter() {
+            let name_c = ffi::CString::new(name.as_bytes()).unwrap();
+            let index = unsafe { ctxt.gl.GetUniformBlockIndex(id, name_c.as_ptr()) };
+
+            if index == gl::INVALID_INDEX {
+                return Err(format!("Uniform block `{}` doesn't exist", name));
+            }
+
+            indices.push((index, binding));
+        }
+
+        for &(index, binding) in indices.iter() {
+            unsafe {
+                ctxt.gl.UniformBlockBinding(id, index, binding as gl::types::GLuint);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the list of transform feedback varyings.
+    pub fn get_transform_feedback_varyings(&self) -> &[TransformFeedbackVarying] {
+        self.varyings.as_ref().map(|&(ref v, _)| &v[..]).unwrap_or(&[])
+    }
+
+    /// Returns informations about a transform feedback varying, if it exists.
+    ///
+    /// Some drivers report arrays with a `[0]` suffix, like uniforms. The suffix is ignored,
+    /// so that both `values` and `values[0]` find the varying named `values[0]` or `values`.
+    pub fn get_transform_feedback_varying(&self, name: &str)
+                                          -> Option<&TransformFeedbackVarying>
+    {
+        let varyings = self.get_transform_feedback_varyings();
+
+        if let Some(varying) = varyings.iter().find(|v| v.name == name) {
+            return Some(varying);
+        }
+
+        let strip = |n: &str| {
+            if n.ends_with("[0]") { n[.. n.len() - 3].to_string() } else { n.to_string() }
+        };
+        let name = strip(name);
+        varyings.iter().find(|v| strip(&v.name) == name)
+    }
+
+    /// Returns the mode used for transform feedback, or `None` is transform feedback is not
+    /// enabled in this program or not supported.
+    pub fn get_transform_feedback_mode(&self) -> Option<TransformFeedbackMode> {
+        self.varyings.as_ref().map(|&(_, m)| m)
+    }
+
+    /// Returns the number of buffers that must be bound in order to capture the output of
+    /// transform feedback with this program.
+    ///
+    /// In separate mode each varying goes into its own buffer, while in interleaved mode all
+    /// the varyings go into a single buffer. Returns `0` if transform feedback is not enabled.
+    pub fn transform_feedback_buffer_count_required(&self) -> usize {
+        match self.varyings {
+            None => 0,
+            Some((ref varyings, TransformFeedbackMode::Separate)) => varyings.len(),
+            Some((ref varyings, TransformFeedbackMode::Interleaved)) => {
+                if varyings.is_empty() { 0 } else { 1 }
+            },
+        }
+    }
+
+    /// Queries the backend to know whether the program is still successfully linked.
+    ///
+    /// A `Program` can't be created without being successfully linked, but some drivers can
+    /// invalidate programs, for example after a context reset. This function can be used to
+    /// detect this situation before drawing.
+    pub fn link_status(&self) -> bool {
+        let mut ctxt = self.context.make_current();
+        unsafe { get_link_status(&mut ctxt, self.id) }
+    }
+
+    /// Asks the driver whether the program can be executed with the current OpenGL state.
+    ///
+    /// This calls `glValidateProgram`. The result depends on the state of the context at the
+    /// time of the call, for example the textures that are bound to the units used by the
+    /// samplers of the program. Glium only sets this state when drawing, so the result of
+    /// this function may not be the same as during a draw command. It is mostly useful when
+    /// debugging. Note that validating replaces the info log of the program.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let mut ctxt = self.context.make_current();
+
+        unsafe {
+            let mut status: gl::types::GLint = 0;
+
+            match self.id {
+                Handle::Id(id) => {
+                    if !(ctxt.version >= &Version(Api::Gl, 2, 0)) &&
+                       !(ctxt.version >= &Version(Api::GlEs, 2, 0))
+                    {
+                        return Err(ValidationError::Unsupported);
+                    }
+
+                    ctxt.gl.ValidateProgram(id);
+                    ctxt.gl.GetProgramiv(id, gl::VALIDATE_STATUS, &mut status);
+                },
+                Handle::Handle(id) => {
+                    if !ctxt.extensions.gl_arb_shader_objects {
+                        return Err(ValidationError::Unsupported);
+                    }
+
+                    ctxt.gl.ValidateProgramARB(id);
+                    ctxt.gl.GetObjectParameterivARB(id, gl::OBJECT_VALIDATE_STATUS_ARB,
+                                                    &mut status);
+                }
+            }
+
+            if status == gl::TRUE as gl::types::GLint {
+                Ok(())
+            } else {
+                Err(ValidationError::Invalid {
+                    status: status as i32,
+                    log: get_program_info_log(&mut ctxt, self.id),
+                })
+            }
+        }
+    }
+
+    /// Returns true if the program contains a tessellation stage.
+    pub fn has_tessellation_shaders(&self) -> bool {
+        self.has_tessellation_shaders
+    }
+
+    /// Returns true if the program is made of the graphics pipeline's stages, used for drawing.
+    ///
+    /// Glium can't create compute programs yet, so this currently always returns `true`.
+    pub fn is_graphics(&self) -> bool {
+        true
+    }
+
+    /// Returns informations about an attribute, if it exists.
+    pub fn get_attribute(&self, name: &str) -> Option<&Attribute> {
+        self.attributes.get(name)
+    }
+
+    /// Checks that an attribute of the program has the given type.
+    ///
+    /// This can be used to validate a vertex format before drawing, as binding a buffer whose
+    /// type doesn't match the attribute usually leads to garbage. Returns an error if the
+    /// attribute doesn't exist or if its type is different.
+    pub fn check_attribute(&self, name: &str, expected: AttributeType)
+                           -> Result<(), AttributeMismatch>
+    {
+        let actual = self.attributes.get(name).map(|a| a.ty);
+
+        if actual == Some(expected) {
+            Ok(())
+        } else {
+            Err(AttributeMismatch {
+                name: name.to_string(),
+                expected: expected,
+                actual: actual,
+            })
+        }
+    }
+
+    /// Returns the location of an attribute, or -1 if it doesn't exist or is inactive.
+    ///
+    /// This is the same value as `glGetAttribLocation`, but it is taken from the reflection
+    /// data and doesn't call OpenGL. This is mostly useful for code that manipulates raw
+    /// OpenGL objects. Programs created with `new_without_reflection` always return -1.
+    pub fn attribute_location(&self, name: &str) -> gl::types::GLint {
+        self.attributes.get(name).map(|a| a.location).unwrap_or(-1)
+    }
+
+    /// Returns the number of vertex attribute slots used by the program, and the maximum
+    /// number of slots supported by the backend.
+    ///
+    /// Matrices use one slot per column, and arrays one slot per element. Some drivers
+    /// successfully link programs that use too many slots but then ignore some attributes,
+    /// so you can use this function to detect this situation.
+    pub fn attribute_slot_usage(&self) -> (usize, usize) {
+        let used = self.attributes.values().fold(0, |total, attribute| {
+            let slots = match attribute.ty {
+                AttributeType::F32x2x2 | AttributeType::F32x2x3 | AttributeType::F32x2x4 => 2,
+                AttributeType::F32x3x2 | AttributeType::F32x3x3 | AttributeType::F32x3x4 => 3,
+                AttributeType::F32x4x2 | AttributeType::F32x4x3 | AttributeType::F32x4x4 => 4,
+                _ => 1,
+            };
+
+            total + slots * attribute.size
+        });
+
+        let max = self.context.capabilities().max_vertex_attribs as usize;
+        (used, max)
+    }
+
+    /// Matches a vertex format against the attributes of the program.
+    ///
+    /// `format` is a list of attribute names and their number of components. Each element
+    /// of the returned list corresponds to the element of `format` at the same position.
+    ///
+    /// Returns an error if an attribute of `format` doesn't exist in the program, if an
+    /// attribute of the program (other than built-ins) is missing from `format`, or if the
+    /// numbers of components don't match.
+    pub fn build_attribute_bindings(&self, format: &[(&str, usize)])
+                                    -> Result<Vec<AttribBinding>, BindingError>
+    {
+        let mut bindings = Vec::with_capacity(format.len());
+
+        for &(name, num_components) in format.iter() {
+            let attribute = match self.attributes.get(name) {
+                Some(a) => a,
+                None => return Err(BindingError::UnknownAttribute(name.to_string()))
+            };
+
+            let expected = attribute.ty.get_num_components();
+            if expected != num_components {
+                return Err(BindingError::ComponentCountMismatch {
+                    name: name.to_string(),
+                    expected: expected,
+                    obtained: num_components,
+                });
+            }
+
+            bindings.push(AttribBinding {
+                name: name.to_string(),
+                location: attribute.location,
+                num_components: num_components,
+                ty: attribute.ty,
+            });
+        }
+
+        for name in self.attributes.keys() {
+            if name.starts_with("gl_") {
+                continue;
+            }
+
+            if !format.iter().any(|&(n, _)| n == &name[..]) {
+                return Err(BindingError::MissingAttribute(name.clone()));
+            }
+        }
+
+        Ok(bindings)
+    }
+
+    /// Returns an iterator to the list of attributes.
+    ///
+    /// Built-in attributes, whose name starts with `gl_`, are not part of this list unless
+    /// `reflect_builtins` was set when creating the program.
+    pub fn attributes(&self) -> hash_map::Iter<String, Attribute> {
+        self.attributes.iter()
     }
-    
-    /// Returns a list of uniform blocks.
-    pub fn get_uniform_blocks(&self) -> &HashMap<String, UniformBlock, DefaultState<FnvHasher>> {
-        &self.uniform_blocks
+
+    /// Returns the number of attributes, ie. the number of elements returned by
+    /// `attributes()`.
+    pub fn attribute_count(&self) -> usize {
+        self.attributes.len()
     }
 
-    /// Returns the list of transform feedback varyings.
-    pub fn get_transform_feedback_varyings(&self) -> &[TransformFeedbackVarying] {
-        self.varyings.as_ref().map(|&(ref v, _)| &v[..]).unwrap_or(&[])
+    /// Returns informations about an output of the last stage of the program, if it exists.
+    ///
+    /// Outputs can only be queried with OpenGL 4.3 or `GL_ARB_program_interface_query`.
+    /// Otherwise this function always returns `None`.
+    pub fn get_output(&self, name: &str) -> Option<&Output> {
+        self.outputs.get(name)
     }
 
-    /// Returns the mode used for transform feedback, or `None` is transform feedback is not
-    /// enabled in this program or not supported.
-    pub fn get_transform_feedback_mode(&self) -> Option<TransformFeedbackMode> {
-        self.varyings.as_ref().map(|&(_, m)| m)
+    /// Returns an iterator to the list of outputs of the last stage of the program.
+    ///
+    /// Outputs can only be queried with OpenGL 4.3 or `GL_ARB_program_interface_query`.
+    /// Otherwise this list is always empty.
+    pub fn outputs(&self) -> hash_map::Iter<String, Output> {
+        self.outputs.iter()
     }
 
-    /// Returns true if the program contains a tessellation stage.
-    pub fn has_tessellation_shaders(&self) -> bool {
-        self.has_tessellation_shaders
+    /// Returns an iterator to the names and locations of the outputs of the fragment shader.
+    ///
+    /// This can be used to match the draw buffers of a framebuffer with the outputs of the
+    /// program. Outputs without a location, like `gl_FragColor`, are skipped.
+    ///
+    /// Outputs can only be enumerated with OpenGL 4.3 or `GL_ARB_program_interface_query`.
+    /// Otherwise this list is always empty, and you have to use `get_frag_data_location`
+    /// instead.
+    pub fn fragment_outputs(&self) -> FragmentOutputsIter {
+        FragmentOutputsIter {
+            iter: if self.is_graphics() { Some(self.outputs.iter()) } else { None },
+        }
     }
 
-    /// Returns informations about an attribute, if it exists.
-    pub fn get_attribute(&self, name: &str) -> Option<&Attribute> {
-        self.attributes.get(name)
+    /// Returns the number of color outputs of the fragment shader, which is the number of
+    /// draw buffers that it writes to.
+    ///
+    /// Each element of an array output is counted separately. Outputs without a location, like
+    /// `gl_FragColor`, are not counted.
+    ///
+    /// Returns `None` if the outputs can't be enumerated, which requires OpenGL 4.3 or
+    /// `GL_ARB_program_interface_query`, or if the program was created without reflection.
+    pub fn fragment_output_count(&self) -> Option<usize> {
+        if !self.reflect || !self.supports_feature(ProgramFeature::InterfaceQuery) {
+            return None;
+        }
+
+        if !self.is_graphics() {
+            return Some(0);
+        }
+
+        Some(self.outputs.values().filter(|output| output.location >= 0)
+                                  .map(|output| output.size).fold(0, |a, b| a + b))
     }
 
-    /// Returns an iterator to the list of attributes.
-    pub fn attributes(&self) -> hash_map::Iter<String, Attribute> {
-        self.attributes.iter()
+    /// Queries properties of a resource of the program with `glGetProgramResourceiv`.
+    ///
+    /// `interface` is the program interface of the resource (for example `GL_UNIFORM` or
+    /// `GL_PROGRAM_INPUT`), and `props` the list of properties to query. This is a low-level
+    /// function meant to access informations that glium doesn't expose. The returned list
+    /// contains the values of the properties in order. Properties that return multiple values,
+    /// like `GL_ACTIVE_VARIABLES`, are not supported.
+    ///
+    /// Returns `None` if the resource doesn't exist, or if OpenGL 4.3 or
+    /// `GL_ARB_program_interface_query` is not supported.
+    pub fn query_resource(&self, interface: gl::types::GLenum, name: &str,
+                          props: &[gl::types::GLenum]) -> Option<Vec<i32>>
+    {
+        let ctxt = self.context.make_current();
+
+        let id = match program_interface_query_id(&ctxt, self.id) {
+            Some(id) => id,
+            None => return None
+        };
+
+        unsafe {
+            let name = ffi::CString::new(name.as_bytes()).unwrap();
+            let index = ctxt.gl.GetProgramResourceIndex(id, interface, name.as_ptr());
+            if index == gl::INVALID_INDEX {
+                return None;
+            }
+
+            let mut values: Vec<gl::types::GLint> = vec![0; props.len()];
+            ctxt.gl.GetProgramResourceiv(id, interface, index,
+                                         props.len() as gl::types::GLsizei, props.as_ptr(),
+                                         values.len() as gl::types::GLsizei, ptr::null_mut(),
+                                         values.as_mut_ptr());
+            Some(values)
+        }
+    }
+}
+
+/// Iterator to the list of uniform blocks of a program.
+pub struct UniformBlocksIter<'a> {
+    iter: hash_map::Iter<'a, String, UniformBlock>,
+}
+
+impl<'a> Iterator for UniformBlocksIter<'a> {
+    type Item = (&'a str, &'a UniformBlock);
+
+    fn next(&mut self) -> Option<(&'a str, &'a UniformBlock)> {
+        self.iter.next().map(|(name, block)| (&name[..], block))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a> ExactSizeIterator for UniformBlocksIter<'a> {}
+
+/// Iterator to the list of uniforms of a program that match a predicate.
+pub struct UniformsMatchingIter<'a, F> {
+    iter: hash_map::Iter<'a, String, Uniform>,
+    predicate: F,
+}
+
+impl<'a, F> Iterator for UniformsMatchingIter<'a, F> where F: Fn(&str, &Uniform) -> bool {
+    type Item = (&'a str, &'a Uniform);
+
+    fn next(&mut self) -> Option<(&'a str, &'a Uniform)> {
+        loop {
+            match self.iter.next() {
+                Some((name, uniform)) if (self.predicate)(name, uniform) => {
+                    return Some((&name[..], uniform));
+                },
+                Some(_) => (),
+                None => return None
+            }
+        }
+    }
+}
+
+/// Iterator to the list of image uniforms of a program.
+pub struct ImageUniformsIter<'a> {
+    iter: hash_map::Iter<'a, String, Uniform>,
+}
+
+impl<'a> Iterator for ImageUniformsIter<'a> {
+    type Item = (&'a str, ImageUniform);
+
+    fn next(&mut self) -> Option<(&'a str, ImageUniform)> {
+        loop {
+            match self.iter.next() {
+                Some((name, uniform)) if is_image(uniform.ty) => {
+                    return Some((&name[..], ImageUniform {
+                        location: uniform.location,
+                        ty: uniform.ty,
+                        size: uniform.size,
+                        binding: uniform.default_binding.unwrap_or(0),
+                    }));
+                },
+                Some(_) => (),
+                None => return None
+            }
+        }
+    }
+}
+
+/// Iterator to the list of sampler uniforms of a program.
+pub struct SamplersIter<'a> {
+    iter: hash_map::Iter<'a, String, Uniform>,
+}
+
+impl<'a> Iterator for SamplersIter<'a> {
+    type Item = (&'a str, SamplerKind, gl::types::GLint);
+
+    fn next(&mut self) -> Option<(&'a str, SamplerKind, gl::types::GLint)> {
+        loop {
+            match self.iter.next() {
+                Some((name, uniform)) => {
+                    if let Some(kind) = sampler_kind(uniform.ty) {
+                        return Some((&name[..], kind, uniform.location));
+                    }
+                },
+                None => return None
+            }
+        }
+    }
+}
+
+/// Iterator to the names and locations of the fragment outputs of a program.
+pub struct FragmentOutputsIter<'a> {
+    iter: Option<hash_map::Iter<'a, String, Output>>,
+}
+
+impl<'a> Iterator for FragmentOutputsIter<'a> {
+    type Item = (&'a str, u32);
+
+    fn next(&mut self) -> Option<(&'a str, u32)> {
+        let iter = match self.iter {
+            Some(ref mut iter) => iter,
+            None => return None
+        };
+
+        loop {
+            match iter.next() {
+                Some((name, output)) if output.location >= 0 => {
+                    return Some((&name[..], output.location as u32));
+                },
+                Some(_) => (),
+                None => return None
+            }
+        }
     }
 }
 
@@ -522,6 +3061,43 @@ impl fmt::Debug for Program {
     }
 }
 
+impl fmt::Display for Program {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        fn plural(n: usize) -> &'static str {
+            if n == 1 { "" } else { "s" }
+        }
+
+        try!(match self.id {
+            Handle::Id(id) => write!(formatter, "Program(#{}, ", id),
+            Handle::Handle(id) => write!(formatter, "Program(#{:?}, ", id),
+        });
+
+        write!(formatter, "{} uniform{}, {} attribute{}, {} block{}, tessellation: {})",
+               self.uniforms.len(), plural(self.uniforms.len()),
+               self.attributes.len(), plural(self.attributes.len()),
+               self.uniform_blocks.len(), plural(self.uniform_blocks.len()),
+               if self.has_tessellation_shaders { "yes" } else { "no" })
+    }
+}
+
+// Two programs are equal if they are the same OpenGL object. Since ids are only unique within
+// a context, the context is compared as well.
+impl PartialEq for Program {
+    fn eq(&self, other: &Program) -> bool {
+        self.id == other.id && &*self.context as *const Context == &*other.context as *const _
+    }
+}
+
+impl Eq for Program {}
+
+// must hash exactly the fields that are compared in `PartialEq`
+impl Hash for Program {
+    fn hash<H>(&self, state: &mut H) where H: Hasher {
+        self.id.hash(state);
+        (&*self.context as *const Context).hash(state);
+    }
+}
+
 impl GlObject for Program {
     type Id = Handle;
     fn get_id(&self) -> Handle {
@@ -531,6 +3107,8 @@ impl GlObject for Program {
 
 impl Drop for Program {
     fn drop(&mut self) {
+        unregister_live_program(&self.context, self.id);
+
         let mut ctxt = self.context.make_current();
 
         // removing VAOs which contain this program
@@ -564,8 +3142,28 @@ impl Drop for Program {
     }
 }
 
+/// Adds a program to the list of live programs of the context.
+#[cfg(feature = "debug_live_programs")]
+fn register_live_program(context: &Context, id: Handle) {
+    context.live_programs.borrow_mut().push(id);
+}
+
+#[cfg(not(feature = "debug_live_programs"))]
+fn register_live_program(_: &Context, _: Handle) {
+}
+
+/// Removes a program from the list of live programs of the context.
+#[cfg(feature = "debug_live_programs")]
+fn unregister_live_program(context: &Context, id: Handle) {
+    context.live_programs.borrow_mut().retain(|&p| p != id);
+}
+
+#[cfg(not(feature = "debug_live_programs"))]
+fn unregister_live_program(_: &Context, _: Handle) {
+}
+
 /// Builds an empty program from within the GL context.
-unsafe fn create_program(ctxt: &mut CommandContext) -> Handle {
+unsafe fn create_program(ctxt: &mut CommandContext) -> Result<Handle, ProgramCreationError> {
     let id = if ctxt.version >= &Version(Api::Gl, 2, 0) {
         Handle::Id(ctxt.gl.CreateProgram())
     } else if ctxt.extensions.gl_arb_shader_objects {
@@ -575,15 +3173,286 @@ unsafe fn create_program(ctxt: &mut CommandContext) -> Handle {
     };
 
     if id == Handle::Id(0) || id == Handle::Handle(0 as gl::types::GLhandleARB) {
-        panic!("glCreateProgram failed");
+        return Err(ProgramCreationError::CreationFailed);
     }
 
-    id
+    Ok(id)
 }
 
-unsafe fn check_program_link_errors(ctxt: &mut CommandContext, id: Handle)
-                                    -> Result<(), ProgramCreationError>
+/// Queries the uniforms, attributes, outputs, uniform blocks and transform feedback varyings
+/// of a linked program.
+///
+/// If `reflect` is `false`, nothing is queried and empty lists are returned instead. Returns
+/// an error if the driver returned invalid data.
+unsafe fn reflect_program(ctxt: &mut CommandContext, id: Handle, reflect: bool,
+                          reflect_builtins: bool)
+                          -> Result<(ReflectionMap<Uniform>,
+                                     ReflectionMap<Attribute>,
+                                     ReflectionMap<Output>,
+                                     ReflectionMap<UniformBlock>,
+                                     Option<(Vec<TransformFeedbackVarying>,
+                                             TransformFeedbackMode)>), String>
+{
+    if !reflect {
+        return Ok((HashMap::with_hash_state(Default::default()),
+                   HashMap::with_hash_state(Default::default()),
+                   HashMap::with_hash_state(Default::default()),
+                   HashMap::with_hash_state(Default::default()),
+                   None));
+    }
+
+    // the same buffer is used to retrieve all the names
+    let mut names = NameBuffer::new();
+
+    Ok((
+        try!(reflect_uniforms(ctxt, id, reflect_builtins, &mut names)),
+        try!(reflect_attributes(ctxt, id, reflect_builtins, &mut names)),
+        try!(reflect_outputs(ctxt, id, reflect_builtins, &mut names)),
+        try!(reflect_uniform_blocks(ctxt, id, &mut names)),
+        try!(reflect_transform_feedback(ctxt, id, &mut names)),
+    ))
+}
+
+/// Destroys a program object that couldn't be turned into a `Program`.
+unsafe fn delete_program_object(ctxt: &mut CommandContext, id: Handle) {
+    match id {
+        Handle::Id(id) => ctxt.gl.DeleteProgram(id),
+        Handle::Handle(id) => ctxt.gl.DeleteObjectARB(id),
+    }
+}
+
+/// Computes a hash of the source code of the stages of a program.
+///
+/// The bytes are written manually instead of using `Hash`, so that the result is the same on
+/// all platforms.
+fn hash_sources(shaders: &[(&str, gl::types::GLenum)]) -> u64 {
+    let mut hasher: FnvHasher = Default::default();
+
+    for &(source, ty) in shaders.iter() {
+        hasher.write(&[(ty >> 24) as u8, (ty >> 16) as u8, (ty >> 8) as u8, ty as u8]);
+        hasher.write(source.as_bytes());
+        hasher.write(&[0xff]);
+    }
+
+    hasher.finish()
+}
+
+/// Returns the value of the `#version` directive of a GLSL source code, if there is one.
+fn get_glsl_version(source: &str) -> Option<&str> {
+    strip_bom(source).lines().map(|line| line.trim())
+          .find(|line| line.starts_with("#version"))
+          .map(|line| line["#version".len() ..].trim())
+}
+
+/// Returns the value of the `max_vertices` layout qualifier of a geometry shader's source
+/// code, if there is one.
+fn get_geometry_max_vertices(source: &str) -> Option<u32> {
+    let position = match source.find("max_vertices") {
+        Some(p) => p,
+        None => return None
+    };
+
+    let rest = source[position + "max_vertices".len() ..].trim_left();
+    if !rest.starts_with("=") {
+        return None;
+    }
+
+    let rest = rest[1 ..].trim_left();
+    let end = rest.find(|c: char| !c.is_digit(10)).unwrap_or(rest.len());
+    rest[.. end].parse().ok()
+}
+
+/// Returns true if the GLSL source code declares an output variable or an output block named
+/// like the transform feedback varying `name`.
+///
+/// Built-in variables and special names like `gl_NextBuffer` are always considered declared.
+fn is_output_declared(source: &str, name: &str) -> bool {
+    if name.starts_with("gl_") {
+        return true;
+    }
+
+    // `block.member` and `array[2]` are declared through `block` and `array`
+    let name = &name[.. name.find(|c: char| c == '.' || c == '[').unwrap_or(name.len())];
+
+    source.split(';').any(|statement| {
+        let tokens = statement.split(|c: char| !c.is_alphanumeric() && c != '_')
+                              .filter(|t| !t.is_empty()).collect::<Vec<_>>();
+
+        (tokens.contains(&"out") || tokens.contains(&"varying")) && tokens.contains(&name)
+    })
+}
+
+/// Returns the first stage that doesn't seem to have any effect, and the reason why.
+///
+/// This is a heuristic based on the source code, and it can't detect every inert stage.
+fn find_inert_stage(shaders: &[(&str, gl::types::GLenum)])
+                    -> Option<(gl::types::GLenum, &'static str)>
 {
+    shaders.iter().filter_map(|&(src, ty)| {
+        let uses = |ident: &str| {
+            src.split(|c: char| !c.is_alphanumeric() && c != '_').any(|t| t == ident)
+        };
+
+        match ty {
+            gl::GEOMETRY_SHADER if !uses("EmitVertex") && !uses("EmitStreamVertex") => {
+                Some((ty, "never calls `EmitVertex`, so it doesn't output any primitive"))
+            },
+            gl::TESS_CONTROL_SHADER if !uses("gl_TessLevelOuter") => {
+                Some((ty, "never writes `gl_TessLevelOuter`, so its patches may be discarded"))
+            },
+            _ => None
+        }
+    }).next()
+}
+
+/// Merges the compilation errors of multiple stages into one.
+fn merge_shader_errors(mut errors: Vec<ShaderError>) -> ShaderError {
+    if errors.len() == 1 {
+        return errors.pop().unwrap();
+    }
+
+    let log = errors.iter().map(|err| {
+        match err.stage {
+            Some(stage) => format!("Errors in the {}:\n{}", shader_type_name(stage.to_glenum()),
+                                   err.log),
+            None => err.log.clone(),
+        }
+    }).collect::<Vec<_>>().connect("\n\n");
+
+    ShaderError {
+        stage: errors[0].stage,
+        log: log,
+    }
+}
+
+/// Returns an estimate of the number of bytes used by the content of a reflection map.
+fn reflection_map_memory<T>(map: &ReflectionMap<T>) -> usize {
+    map.capacity() * (mem::size_of::<String>() + mem::size_of::<T>()) +
+        map.keys().fold(0, |total, name| total + name.capacity())
+}
+
+/// Returns true if a source code contains a `layout(early_fragment_tests) in;` declaration.
+fn declares_early_fragment_tests(source: &str) -> bool {
+    source.split(';').any(|statement| {
+        let tokens = statement.split(|c: char| !c.is_alphanumeric() && c != '_')
+                              .filter(|t| !t.is_empty()).collect::<Vec<_>>();
+
+        // the statement may also contain the preprocessor directives that precede it
+        tokens.ends_with(&["layout", "early_fragment_tests", "in"])
+    })
+}
+
+/// Returns true if the source code contains an assignment to `gl_PointSize`.
+fn assigns_point_size(source: &str) -> bool {
+    source.match_indices("gl_PointSize").any(|(start, end)| {
+        // ignoring longer identifiers that start with the same characters
+        let before = source[.. start].chars().rev().next();
+        if before.map(|c| c.is_alphanumeric() || c == '_').unwrap_or(false) {
+            return false;
+        }
+
+        let after = source[end ..].trim_left();
+        after.starts_with("=") && !after.starts_with("==")
+    })
+}
+
+/// Returns the names of the extensions that the source code enables with an `#extension`
+/// directive whose behavior is `require`.
+fn required_extensions(source: &str) -> Vec<&str> {
+    strip_bom(source).lines().filter_map(|line| {
+        let line = line.trim();
+        if !line.starts_with("#") {
+            return None;
+        }
+
+        let line = line[1 ..].trim_left();
+        if !line.starts_with("extension") {
+            return None;
+        }
+
+        // ignoring trailing comments
+        let line = &line["extension".len() ..];
+        let line = match line.find("//") {
+            Some(pos) => &line[.. pos],
+            None => line
+        };
+
+        let mut parts = line.splitn(2, ':');
+        let name = match parts.next() { Some(n) => n.trim(), None => return None };
+        let behavior = match parts.next() { Some(b) => b.trim(), None => return None };
+
+        // `all` can't be required
+        if behavior == "require" && name != "all" {
+            Some(name)
+        } else {
+            None
+        }
+    }).collect()
+}
+
+/// Returns true if the source code contains a `precision ... float;` statement.
+fn declares_float_precision(source: &str) -> bool {
+    source.split(';').any(|statement| {
+        let tokens = statement.split(|c: char| !c.is_alphanumeric() && c != '_')
+                              .filter(|t| !t.is_empty()).collect::<Vec<_>>();
+
+        tokens.len() >= 3 && tokens[tokens.len() - 3] == "precision" &&
+        tokens[tokens.len() - 1] == "float"
+    })
+}
+
+/// Splits an info log into lines and drops the lines that are obviously not warnings.
+fn parse_log_warnings(log: &str) -> Vec<String> {
+    log.lines().map(|line| line.trim()).filter(|line| {
+        !line.is_empty() && *line != "No errors." && !line.contains("successfully") &&
+        !line.ends_with("linked.")
+    }).map(|line| line.to_string()).collect()
+}
+
+/// Returns a human-readable name for a type of shader.
+fn shader_type_name(ty: gl::types::GLenum) -> &'static str {
+    match ty {
+        gl::VERTEX_SHADER => "vertex shader",
+        gl::TESS_CONTROL_SHADER => "tessellation control shader",
+        gl::TESS_EVALUATION_SHADER => "tessellation evaluation shader",
+        gl::GEOMETRY_SHADER => "geometry shader",
+        gl::FRAGMENT_SHADER => "fragment shader",
+        gl::COMPUTE_SHADER => "compute shader",
+        gl::MESH_SHADER_NV => "mesh shader",
+        gl::TASK_SHADER_NV => "task shader",
+        _ => "unknown shader"
+    }
+}
+
+/// Extracts the assembly programs embedded in a program binary generated by nVidia drivers.
+///
+/// Each program starts with a `!!NV` header and ends with an `END` line.
+fn extract_nv_assembly(binary: &[u8]) -> Option<String> {
+    let mut result = String::new();
+    let mut remaining = binary;
+
+    while let Some(start) = remaining.windows(4).position(|w| w == b"!!NV") {
+        let program = &remaining[start ..];
+        let end = program.windows(4).position(|w| w == b"END\n").map(|e| e + 3)
+                         .unwrap_or(program.len());
+
+        if let Ok(text) = str::from_utf8(&program[.. end]) {
+            result.push_str(text);
+            result.push('\n');
+        }
+
+        remaining = &program[end ..];
+    }
+
+    if result.len() == 0 {
+        None
+    } else {
+        Some(result)
+    }
+}
+
+/// Returns the value of `GL_LINK_STATUS` for a program.
+unsafe fn get_link_status(ctxt: &mut CommandContext, id: Handle) -> bool {
     let mut link_success: gl::types::GLint = mem::uninitialized();
 
     match id {
@@ -598,59 +3467,102 @@ unsafe fn check_program_link_errors(ctxt: &mut CommandContext, id: Handle)
         }
     }
 
-    if link_success == 0 {
-        use ProgramCreationError::LinkingError;
-
-        match ctxt.gl.GetError() {
-            gl::NO_ERROR => (),
-            gl::INVALID_VALUE => {
-                return Err(LinkingError(format!("glLinkProgram triggered \
-                                                 GL_INVALID_VALUE")));
-            },
-            gl::INVALID_OPERATION => {
-                return Err(LinkingError(format!("glLinkProgram triggered \
-                                                 GL_INVALID_OPERATION")));
-            },
-            _ => {
-                return Err(LinkingError(format!("glLinkProgram triggered an \
-                                                 unknown error")));
-            }
-        };
+    link_success != 0
+}
 
-        let mut error_log_size: gl::types::GLint = mem::uninitialized();
+/// Returns the info log of a program.
+unsafe fn get_program_info_log(ctxt: &mut CommandContext, id: Handle) -> String {
+    let mut error_log_size: gl::types::GLint = mem::uninitialized();
 
-        match id {
-            Handle::Id(id) => {
-                assert!(ctxt.version >= &Version(Api::Gl, 2, 0));
-                ctxt.gl.GetProgramiv(id, gl::INFO_LOG_LENGTH, &mut error_log_size);
-            },
-            Handle::Handle(id) => {
-                assert!(ctxt.extensions.gl_arb_shader_objects);
-                ctxt.gl.GetObjectParameterivARB(id, gl::OBJECT_INFO_LOG_LENGTH_ARB,
-                                                &mut error_log_size);
-            }
+    match id {
+        Handle::Id(id) => {
+            assert!(ctxt.version >= &Version(Api::Gl, 2, 0));
+            ctxt.gl.GetProgramiv(id, gl::INFO_LOG_LENGTH, &mut error_log_size);
+        },
+        Handle::Handle(id) => {
+            assert!(ctxt.extensions.gl_arb_shader_objects);
+            ctxt.gl.GetObjectParameterivARB(id, gl::OBJECT_INFO_LOG_LENGTH_ARB,
+                                            &mut error_log_size);
         }
+    }
 
-        let mut error_log: Vec<u8> = Vec::with_capacity(error_log_size as usize);
+    let mut error_log: Vec<u8> = Vec::with_capacity(error_log_size as usize);
 
-        match id {
-            Handle::Id(id) => {
-                assert!(ctxt.version >= &Version(Api::Gl, 2, 0));
-                ctxt.gl.GetProgramInfoLog(id, error_log_size, &mut error_log_size,
-                                          error_log.as_mut_ptr() as *mut gl::types::GLchar);
-            },
-            Handle::Handle(id) => {
-                assert!(ctxt.extensions.gl_arb_shader_objects);
-                ctxt.gl.GetInfoLogARB(id, error_log_size, &mut error_log_size,
+    match id {
+        Handle::Id(id) => {
+            assert!(ctxt.version >= &Version(Api::Gl, 2, 0));
+            ctxt.gl.GetProgramInfoLog(id, error_log_size, &mut error_log_size,
                                       error_log.as_mut_ptr() as *mut gl::types::GLchar);
-            }
+        },
+        Handle::Handle(id) => {
+            assert!(ctxt.extensions.gl_arb_shader_objects);
+            ctxt.gl.GetInfoLogARB(id, error_log_size, &mut error_log_size,
+                                  error_log.as_mut_ptr() as *mut gl::types::GLchar);
         }
+    }
+
+    error_log.set_len(error_log_size as usize);
+
+    match String::from_utf8(error_log) {
+        Ok(msg) => msg,
+        Err(_) => "Could not convert the log message to UTF-8".to_string(),
+    }
+}
 
-        error_log.set_len(error_log_size as usize);
+unsafe fn check_program_link_errors(ctxt: &mut CommandContext, id: Handle)
+                                    -> Result<(), ProgramCreationError>
+{
+    if !get_link_status(ctxt, id) {
+        let log = match ctxt.gl.GetError() {
+            gl::NO_ERROR => get_program_info_log(ctxt, id),
+            gl::INVALID_VALUE => format!("glLinkProgram triggered GL_INVALID_VALUE"),
+            gl::INVALID_OPERATION => format!("glLinkProgram triggered GL_INVALID_OPERATION"),
+            _ => format!("glLinkProgram triggered an unknown error"),
+        };
 
-        let msg = String::from_utf8(error_log).unwrap();
-        return Err(LinkingError(msg));
+        return Err(ProgramCreationError::LinkingError(ShaderError {
+            stage: None,
+            log: log,
+        }));
     }
 
     Ok(())
 }
+
+/// Returns the input primitive of the geometry shader of a program, or `None` if it doesn't
+/// have one.
+///
+/// The shaders of a program stay attached to it after linking, which is how we know whether
+/// there is a geometry shader. Programs loaded from a binary have no attached shader.
+unsafe fn geometry_input_type(ctxt: &mut CommandContext, program: Handle)
+                              -> Option<gl::types::GLenum>
+{
+    let id = match program {
+        Handle::Id(id) => id,
+        Handle::Handle(_) => return None,
+    };
+
+    if !(ctxt.version >= &Version(Api::Gl, 3, 2)) {
+        return None;
+    }
+
+    let mut count = 0;
+    ctxt.gl.GetProgramiv(id, gl::ATTACHED_SHADERS, &mut count);
+
+    let mut shaders: Vec<gl::types::GLuint> = vec![0; count as usize];
+    ctxt.gl.GetAttachedShaders(id, count, ptr::null_mut(), shaders.as_mut_ptr());
+
+    let has_geometry_shader = shaders.iter().any(|&shader| {
+        let mut ty = 0;
+        ctxt.gl.GetShaderiv(shader, gl::SHADER_TYPE, &mut ty);
+        ty as gl::types::GLenum == gl::GEOMETRY_SHADER
+    });
+
+    if !has_geometry_shader {
+        return None;
+    }
+
+    let mut ty = 0;
+    ctxt.gl.GetProgramiv(id, gl::GEOMETRY_INPUT_TYPE, &mut ty);
+    Some(ty as gl::types::GLenum)
+}
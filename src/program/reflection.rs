@@ -3,6 +3,8 @@ use libc;
 
 use std::ffi;
 use std::mem;
+use std::ptr;
+use std::str;
 use std::collections::hash_state::DefaultState;
 use std::collections::HashMap;
 use std::default::Default;
@@ -12,24 +14,89 @@ use context::CommandContext;
 use version::Version;
 use version::Api;
 
-use uniforms::UniformType;
+use uniforms::{UniformType, UniformValue};
 use vertex::AttributeType;
+use program::ShaderStage;
 
 use Handle;
 
+/// Map from names to reflection informations.
+///
+/// This is an implementation detail of glium. Use this alias instead of naming the type of the
+/// map so that the hasher can be changed without modifying signatures.
+pub type ReflectionMap<T> = HashMap<String, T, DefaultState<FnvHasher>>;
+
+/// Buffer that is reused to retrieve the names of all the resources of a program, in order to
+/// avoid allocating a new buffer for each name.
+pub struct NameBuffer {
+    data: Vec<u8>,
+}
+
+impl NameBuffer {
+    /// Builds an empty buffer. It grows when needed.
+    pub fn new() -> NameBuffer {
+        NameBuffer { data: Vec::new() }
+    }
+
+    /// Makes sure that the buffer can hold a name of `max_len` bytes, and returns the size of
+    /// the buffer and a pointer to it.
+    fn prepare(&mut self, max_len: gl::types::GLint)
+               -> (gl::types::GLsizei, *mut gl::types::GLchar)
+    {
+        // the max length is supposed to include the null terminator, but some drivers
+        // don't count it, so we add one more byte to avoid truncating the names
+        let size = 1 + if max_len > 0 { max_len as usize } else { 0 };
+
+        self.data.clear();
+        self.data.reserve(size);
+        (size as gl::types::GLsizei, self.data.as_mut_ptr() as *mut gl::types::GLchar)
+    }
+
+    /// Returns the name that has been written in the buffer by OpenGL.
+    ///
+    /// Returns an error if the length returned by OpenGL doesn't fit in the buffer, or if the
+    /// name is not valid UTF-8.
+    unsafe fn get(&mut self, len: gl::types::GLsizei) -> Result<String, String> {
+        if len < 0 || len as usize > self.data.capacity() {
+            return Err(format!("OpenGL returned a name length of {} for a buffer of {} bytes",
+                               len, self.data.capacity()));
+        }
+
+        self.data.set_len(len as usize);
+
+        match str::from_utf8(&self.data) {
+            Ok(name) => Ok(name.to_string()),
+            Err(_) => Err(format!("OpenGL returned a name that is not valid UTF-8: {:?}",
+                                  self.data)),
+        }
+    }
+}
+
 /// Information about a uniform (except its name).
+///
+/// Returned by `Program::get_uniform` and `Program::uniforms`. All the fields are public and
+/// part of the stable API.
 #[derive(Debug, Copy)]
 pub struct Uniform {
-    /// The location of the uniform.
+    /// The location of the uniform, as returned by `glGetUniformLocation`.
     ///
-    /// This is internal information, you probably don't need to use it.
+    /// You only need this if you manipulate the program with raw OpenGL calls.
     pub location: i32,
 
-    /// Type of the uniform.
+    /// Type of the uniform. For arrays, this is the type of each element.
     pub ty: UniformType,
 
-    /// If it is an array, the number of elements.
+    /// If it is an array, the number of elements. `None` if it is not an array.
+    ///
+    /// Note that drivers can remove the elements at the end of an array if they are not used.
     pub size: Option<usize>,
+
+    /// For samplers and images, the texture or image unit that the uniform is bound to
+    /// after linking.
+    ///
+    /// This is the value specified with `layout(binding = N)` in the shader, or `0` if
+    /// there is none. `None` for other types of uniforms.
+    pub default_binding: Option<u32>,
 }
 
 /// Information about a uniform block (except its name).
@@ -45,6 +112,45 @@ pub struct UniformBlock {
 
     /// List of elements in the block.
     pub members: Vec<UniformBlockMember>,
+
+    /// The stages of the program that reference the block.
+    pub stages: StageMask,
+}
+
+/// A set of stages of a program.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct StageMask {
+    /// The vertex shader.
+    pub vertex: bool,
+    /// The tessellation control shader.
+    pub tessellation_control: bool,
+    /// The tessellation evaluation shader.
+    pub tessellation_evaluation: bool,
+    /// The geometry shader.
+    pub geometry: bool,
+    /// The fragment shader.
+    pub fragment: bool,
+    /// The compute shader.
+    pub compute: bool,
+}
+
+impl StageMask {
+    /// Returns true if the set contains the given stage.
+    pub fn contains(&self, stage: ShaderStage) -> bool {
+        match stage {
+            ShaderStage::Vertex => self.vertex,
+            ShaderStage::TessellationControl => self.tessellation_control,
+            ShaderStage::TessellationEvaluation => self.tessellation_evaluation,
+            ShaderStage::Geometry => self.geometry,
+            ShaderStage::Fragment => self.fragment,
+            ShaderStage::Mesh | ShaderStage::Task => false,
+        }
+    }
+
+    /// Returns true if the set doesn't contain any stage.
+    pub fn is_empty(&self) -> bool {
+        *self == StageMask::default()
+    }
 }
 
 /// Information about a uniform inside a block.
@@ -56,6 +162,14 @@ pub struct UniformBlockMember {
     /// Offset of the member in the block.
     pub offset: usize,
 
+    /// If it is an array, the number of bytes between the start of two consecutive elements.
+    /// Otherwise `0`.
+    pub array_stride: usize,
+
+    /// If it is a matrix, the number of bytes between the start of two consecutive columns
+    /// (or rows for row-major matrices). Otherwise `0`.
+    pub matrix_stride: usize,
+
     /// Type of the uniform.
     pub ty: UniformType,
 
@@ -78,6 +192,113 @@ pub struct Attribute {
 
     /// Number of elements of the attribute.
     pub size: usize,
+
+    /// The first component of the location that the attribute occupies, as specified with
+    /// the `component` layout qualifier.
+    ///
+    /// `None` if the backend doesn't support querying this information.
+    pub component: Option<u32>,
+}
+
+/// Information about an output of a program (except its name).
+#[derive(Debug, Copy, Clone)]
+pub struct Output {
+    /// The location of the output.
+    pub location: i32,
+
+    /// The first component of the location that the output occupies, as specified with
+    /// the `component` layout qualifier.
+    ///
+    /// `None` if the backend doesn't support querying this information.
+    pub component: Option<u32>,
+
+    /// Type of the output.
+    pub ty: AttributeType,
+
+    /// Number of elements of the output.
+    pub size: usize,
+}
+
+/// Information about an image uniform, ie. a uniform that must be bound with
+/// `glBindImageTexture` instead of a texture unit.
+#[derive(Debug, Copy, Clone)]
+pub struct ImageUniform {
+    /// The location of the uniform.
+    ///
+    /// This is internal information, you probably don't need to use it.
+    pub location: i32,
+
+    /// Type of the image.
+    pub ty: UniformType,
+
+    /// If it is an array, the number of elements.
+    pub size: Option<usize>,
+
+    /// The image unit that the uniform is bound to after linking.
+    ///
+    /// This is the value specified with `layout(binding = N)` in the shader, or `0` if
+    /// there is none.
+    ///
+    /// Note that the format of the image (for example `rgba8`) can't be queried from OpenGL.
+    pub binding: u32,
+}
+
+/// Kind of texture that a sampler uniform reads from.
+///
+/// Signed and unsigned integer samplers, like `isampler2D`, have the same kind as their
+/// floating-point equivalent.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SamplerKind {
+    /// `sampler1D`.
+    Texture1d,
+    /// `sampler2D`.
+    Texture2d,
+    /// `sampler3D`.
+    Texture3d,
+    /// `sampler1DArray`.
+    Texture1dArray,
+    /// `sampler2DArray`.
+    Texture2dArray,
+    /// `samplerCube`.
+    Cubemap,
+    /// `samplerCubeArray`.
+    CubemapArray,
+    /// `sampler2DRect`.
+    Texture2dRect,
+    /// `samplerBuffer`.
+    Buffer,
+    /// `sampler2DMS`.
+    Texture2dMultisample,
+    /// `sampler2DMSArray`.
+    Texture2dMultisampleArray,
+    /// `sampler1DShadow`.
+    Shadow1d,
+    /// `sampler2DShadow`.
+    Shadow2d,
+    /// `samplerCubeShadow`.
+    ShadowCubemap,
+    /// `sampler1DArrayShadow`.
+    Shadow1dArray,
+    /// `sampler2DArrayShadow`.
+    Shadow2dArray,
+    /// `samplerCubeArrayShadow`.
+    ShadowCubemapArray,
+    /// `sampler2DRectShadow`.
+    Shadow2dRect,
+}
+
+impl SamplerKind {
+    /// Returns true if this is a shadow sampler.
+    ///
+    /// Shadow samplers must be used with depth textures whose comparison mode is enabled.
+    pub fn is_shadow(&self) -> bool {
+        match *self {
+            SamplerKind::Shadow1d | SamplerKind::Shadow2d | SamplerKind::ShadowCubemap |
+            SamplerKind::Shadow1dArray | SamplerKind::Shadow2dArray |
+            SamplerKind::ShadowCubemapArray | SamplerKind::Shadow2dRect => true,
+            _ => false,
+        }
+    }
 }
 
 /// Describes a varying that is being output with transform feedback.
@@ -89,7 +310,16 @@ pub struct TransformFeedbackVarying {
     /// Size in bytes of this value.
     pub size: usize,
 
-    /// Type of the value.
+    /// Offset in bytes of this value in its buffer.
+    ///
+    /// In interleaved mode, this is the sum of the sizes of the previous varyings. In separate
+    /// mode, each varying has its own buffer and this is always `0`.
+    pub offset: usize,
+
+    /// Total number of components of this value, including all the elements of arrays.
+    pub num_components: usize,
+
+    /// Type of the value. For arrays, this is the type of each element.
     pub ty: AttributeType,
 }
 
@@ -103,12 +333,175 @@ pub enum TransformFeedbackMode {
     Separate,
 }
 
-pub unsafe fn reflect_uniforms(ctxt: &mut CommandContext, program: Handle)
-                               -> HashMap<String, Uniform, DefaultState<FnvHasher>>
+/// Reflects the list of active uniforms of a program.
+///
+/// Built-in uniforms are skipped unless `keep_builtins` is `true`.
+pub unsafe fn reflect_uniforms(ctxt: &mut CommandContext, program: Handle, keep_builtins: bool,
+                               names: &mut NameBuffer) -> Result<ReflectionMap<Uniform>, String>
 {
     // reflecting program uniforms
     let mut uniforms = HashMap::with_hash_state(Default::default());
 
+    // list of the names, types and array sizes of the active uniforms
+    let list = match program_interface_query_id(ctxt, program) {
+        Some(id) => try!(list_resources_with_type_and_size(ctxt, id, gl::UNIFORM, names)),
+        None => try!(list_active_uniforms(ctxt, program, names)),
+    };
+
+    for (uniform_name, data_type, data_size) in list.into_iter() {
+        if !keep_builtins && uniform_name.starts_with("gl_") {
+            continue;
+        }
+
+        let c_name = try!(to_c_string(&uniform_name));
+        let location = match program {
+            Handle::Id(program) => {
+                assert!(ctxt.version >= &Version(Api::Gl, 2, 0));
+                ctxt.gl.GetUniformLocation(program, c_name.as_bytes_with_nul().as_ptr()
+                                                      as *const libc::c_char)
+            },
+            Handle::Handle(program) => {
+                assert!(ctxt.extensions.gl_arb_shader_objects);
+                ctxt.gl.GetUniformLocationARB(program, c_name.as_bytes_with_nul().as_ptr()
+                                                         as *const libc::c_char)
+            }
+        };
+
+        let ty = try!(glenum_to_uniform_type(data_type));
+
+        // the initial value of samplers and images is their binding point
+        let default_binding = if location != -1 && is_sampler_or_image(ty) {
+            let mut value: gl::types::GLint = 0;
+
+            match program {
+                Handle::Id(program) => {
+                    ctxt.gl.GetUniformiv(program, location, &mut value);
+                },
+                Handle::Handle(program) => {
+                    ctxt.gl.GetUniformivARB(program, location, &mut value);
+                }
+            };
+
+            Some(value as u32)
+
+        } else {
+            None
+        };
+
+        uniforms.insert(uniform_name, Uniform {
+            location: location as i32,
+            ty: ty,
+            size: if data_size == 1 { None } else { Some(data_size as usize) },
+            default_binding: default_binding,
+        });
+    }
+
+    Ok(uniforms)
+}
+
+/// Queries the values of the uniforms of a program that has just been linked, which are the
+/// values of their initializers in the source code, or zero.
+///
+/// Only uniforms that are not arrays and whose type corresponds to a `UniformValue` are
+/// queried.
+pub unsafe fn reflect_uniform_defaults(ctxt: &mut CommandContext, program: Handle,
+                                       uniforms: &ReflectionMap<Uniform>)
+                                       -> ReflectionMap<UniformValue<'static>>
+{
+    let mut defaults = HashMap::with_hash_state(Default::default());
+
+    for (name, uniform) in uniforms.iter() {
+        if uniform.location == -1 || uniform.size.is_some() {
+            continue;
+        }
+
+        let location = uniform.location;
+
+        let value = match uniform.ty {
+            UniformType::Float | UniformType::FloatVec2 | UniformType::FloatVec3 |
+            UniformType::FloatVec4 | UniformType::FloatMat2 | UniformType::FloatMat3 |
+            UniformType::FloatMat4 => {
+                let mut data: [f32; 16] = [0.0; 16];
+
+                match program {
+                    Handle::Id(program) => {
+                        ctxt.gl.GetUniformfv(program, location, data.as_mut_ptr());
+                    },
+                    Handle::Handle(program) => {
+                        ctxt.gl.GetUniformfvARB(program, location, data.as_mut_ptr());
+                    }
+                };
+
+                // matrices are returned in column-major order, like `UniformValue` stores them
+                match uniform.ty {
+                    UniformType::Float => UniformValue::Float(data[0]),
+                    UniformType::FloatVec2 => UniformValue::Vec2([data[0], data[1]]),
+                    UniformType::FloatVec3 => UniformValue::Vec3([data[0], data[1], data[2]]),
+                    UniformType::FloatVec4 => {
+                        UniformValue::Vec4([data[0], data[1], data[2], data[3]])
+                    },
+                    UniformType::FloatMat2 => {
+                        UniformValue::Mat2([[data[0], data[1]], [data[2], data[3]]])
+                    },
+                    UniformType::FloatMat3 => {
+                        UniformValue::Mat3([[data[0], data[1], data[2]],
+                                            [data[3], data[4], data[5]],
+                                            [data[6], data[7], data[8]]])
+                    },
+                    UniformType::FloatMat4 => {
+                        UniformValue::Mat4([[data[0], data[1], data[2], data[3]],
+                                            [data[4], data[5], data[6], data[7]],
+                                            [data[8], data[9], data[10], data[11]],
+                                            [data[12], data[13], data[14], data[15]]])
+                    },
+                    _ => unreachable!()
+                }
+            },
+
+            UniformType::Int => {
+                let mut value: gl::types::GLint = 0;
+
+                match program {
+                    Handle::Id(program) => {
+                        ctxt.gl.GetUniformiv(program, location, &mut value);
+                    },
+                    Handle::Handle(program) => {
+                        ctxt.gl.GetUniformivARB(program, location, &mut value);
+                    }
+                };
+
+                UniformValue::SignedInt(value)
+            },
+
+            UniformType::UnsignedInt => {
+                // `glGetUniformuiv` has no ARB equivalent
+                let program = match program {
+                    Handle::Id(program) if ctxt.version >= &Version(Api::Gl, 3, 0) ||
+                                           ctxt.version >= &Version(Api::GlEs, 3, 0) => program,
+                    _ => continue
+                };
+
+                let mut value: gl::types::GLuint = 0;
+                ctxt.gl.GetUniformuiv(program, location, &mut value);
+                UniformValue::UnsignedInt(value)
+            },
+
+            _ => continue
+        };
+
+        defaults.insert(name.clone(), value);
+    }
+
+    defaults
+}
+
+/// Lists the names, types and array sizes of the active uniforms of a program
+/// with `glGetActiveUniform`.
+unsafe fn list_active_uniforms(ctxt: &mut CommandContext, program: Handle,
+                               names: &mut NameBuffer)
+                               -> Result<Vec<(String, gl::types::GLenum, gl::types::GLint)>,
+                                         String>
+{
     // number of active uniforms
     let active_uniforms = {
         let mut active_uniforms: gl::types::GLint = mem::uninitialized();
@@ -126,9 +519,29 @@ pub unsafe fn reflect_uniforms(ctxt: &mut CommandContext, program: Handle)
         active_uniforms
     };
 
+    // the max length includes the null terminator
+    let max_name_len = {
+        let mut max_name_len: gl::types::GLint = mem::uninitialized();
+        match program {
+            Handle::Id(program) => {
+                assert!(ctxt.version >= &Version(Api::Gl, 2, 0));
+                ctxt.gl.GetProgramiv(program, gl::ACTIVE_UNIFORM_MAX_LENGTH, &mut max_name_len);
+            },
+            Handle::Handle(program) => {
+                assert!(ctxt.extensions.gl_arb_shader_objects);
+                ctxt.gl.GetObjectParameterivARB(program,
+                                                gl::OBJECT_ACTIVE_UNIFORM_MAX_LENGTH_ARB,
+                                                &mut max_name_len);
+            }
+        };
+        max_name_len
+    };
+
+    let mut result = Vec::with_capacity(active_uniforms as usize);
+
     for uniform_id in (0 .. active_uniforms) {
-        let mut uniform_name_tmp: Vec<u8> = Vec::with_capacity(64);
-        let mut uniform_name_tmp_len = 63;
+        let (buffer_size, buffer) = names.prepare(max_name_len);
+        let mut name_len = 0;
 
         let mut data_type: gl::types::GLenum = mem::uninitialized();
         let mut data_size: gl::types::GLint = mem::uninitialized();
@@ -137,53 +550,79 @@ pub unsafe fn reflect_uniforms(ctxt: &mut CommandContext, program: Handle)
             Handle::Id(program) => {
                 assert!(ctxt.version >= &Version(Api::Gl, 2, 0));
                 ctxt.gl.GetActiveUniform(program, uniform_id as gl::types::GLuint,
-                                         uniform_name_tmp_len, &mut uniform_name_tmp_len,
-                                         &mut data_size, &mut data_type,
-                                         uniform_name_tmp.as_mut_ptr() as *mut gl::types::GLchar);
+                                         buffer_size, &mut name_len,
+                                         &mut data_size, &mut data_type, buffer);
             },
             Handle::Handle(program) => {
                 assert!(ctxt.extensions.gl_arb_shader_objects);
                 ctxt.gl.GetActiveUniformARB(program, uniform_id as gl::types::GLuint,
-                                            uniform_name_tmp_len, &mut uniform_name_tmp_len,
-                                            &mut data_size, &mut data_type,
-                                            uniform_name_tmp.as_mut_ptr()
-                                              as *mut gl::types::GLchar);
+                                            buffer_size, &mut name_len,
+                                            &mut data_size, &mut data_type, buffer);
             }
         };
 
-        uniform_name_tmp.set_len(uniform_name_tmp_len as usize);
+        let uniform_name = try!(names.get(name_len));
+        result.push((uniform_name, data_type, data_size));
+    }
+
+    Ok(result)
+}
 
-        let uniform_name = String::from_utf8(uniform_name_tmp).unwrap();
+/// Reflects the list of active attributes of a program.
+///
+/// Built-in attributes are skipped unless `keep_builtins` is `true`.
+pub unsafe fn reflect_attributes(ctxt: &mut CommandContext, program: Handle, keep_builtins: bool,
+                                 names: &mut NameBuffer)
+                                 -> Result<ReflectionMap<Attribute>, String>
+{
+    let mut attributes = HashMap::with_hash_state(Default::default());
+
+    // list of the names, types and array sizes of the active attributes
+    let list = match program_interface_query_id(ctxt, program) {
+        Some(id) => try!(list_resources_with_type_and_size(ctxt, id, gl::PROGRAM_INPUT,
+                                                           names)),
+        None => try!(list_active_attributes(ctxt, program, names)),
+    };
+
+    for (attr_name, data_type, data_size) in list.into_iter() {
+        if !keep_builtins && attr_name.starts_with("gl_") {   // ignoring everything built-in
+            continue;
+        }
+
+        let c_name = try!(to_c_string(&attr_name));
         let location = match program {
             Handle::Id(program) => {
                 assert!(ctxt.version >= &Version(Api::Gl, 2, 0));
-                ctxt.gl.GetUniformLocation(program,
-                                           ffi::CString::new(uniform_name.as_bytes()).unwrap()
-                                             .as_bytes_with_nul().as_ptr() as *const libc::c_char)
+                ctxt.gl.GetAttribLocation(program, c_name.as_bytes_with_nul().as_ptr()
+                                                     as *const libc::c_char)
             },
             Handle::Handle(program) => {
-                assert!(ctxt.extensions.gl_arb_shader_objects);
-                ctxt.gl.GetUniformLocationARB(program,
-                                              ffi::CString::new(uniform_name.as_bytes()).unwrap()
-                                                .as_bytes_with_nul().as_ptr() as *const libc::c_char)
+                assert!(ctxt.extensions.gl_arb_vertex_shader);
+                ctxt.gl.GetAttribLocationARB(program, c_name.as_bytes_with_nul().as_ptr()
+                                                        as *const libc::c_char)
             }
         };
 
-        uniforms.insert(uniform_name, Uniform {
-            location: location as i32,
-            ty: glenum_to_uniform_type(data_type),
-            size: if data_size == 1 { None } else { Some(data_size as usize) },
+        let component = get_location_component(ctxt, program, gl::PROGRAM_INPUT, &attr_name);
+
+        attributes.insert(attr_name, Attribute {
+            location: location,
+            ty: try!(glenum_to_attribute_type(data_type)),
+            size: data_size as usize,
+            component: component,
         });
     }
 
-    uniforms
+    Ok(attributes)
 }
 
-pub unsafe fn reflect_attributes(ctxt: &mut CommandContext, program: Handle)
-                                 -> HashMap<String, Attribute, DefaultState<FnvHasher>>
+/// Lists the names, types and array sizes of the active attributes of a program
+/// with `glGetActiveAttrib`.
+unsafe fn list_active_attributes(ctxt: &mut CommandContext, program: Handle,
+                                 names: &mut NameBuffer)
+                                 -> Result<Vec<(String, gl::types::GLenum, gl::types::GLint)>,
+                                           String>
 {
-    let mut attributes = HashMap::with_hash_state(Default::default());
-
     // number of active attributes
     let active_attributes = {
         let mut active_attributes: gl::types::GLint = mem::uninitialized();
@@ -201,9 +640,29 @@ pub unsafe fn reflect_attributes(ctxt: &mut CommandContext, program: Handle)
         active_attributes
     };
 
+    // the max length includes the null terminator
+    let max_name_len = {
+        let mut max_name_len: gl::types::GLint = mem::uninitialized();
+        match program {
+            Handle::Id(program) => {
+                assert!(ctxt.version >= &Version(Api::Gl, 2, 0));
+                ctxt.gl.GetProgramiv(program, gl::ACTIVE_ATTRIBUTE_MAX_LENGTH, &mut max_name_len);
+            },
+            Handle::Handle(program) => {
+                assert!(ctxt.extensions.gl_arb_vertex_shader);
+                ctxt.gl.GetObjectParameterivARB(program,
+                                                gl::OBJECT_ACTIVE_ATTRIBUTE_MAX_LENGTH_ARB,
+                                                &mut max_name_len);
+            }
+        };
+        max_name_len
+    };
+
+    let mut result = Vec::with_capacity(active_attributes as usize);
+
     for attribute_id in (0 .. active_attributes) {
-        let mut attr_name_tmp: Vec<u8> = Vec::with_capacity(64);
-        let mut attr_name_tmp_len = 63;
+        let (buffer_size, buffer) = names.prepare(max_name_len);
+        let mut name_len = 0;
 
         let mut data_type: gl::types::GLenum = mem::uninitialized();
         let mut data_size: gl::types::GLint = mem::uninitialized();
@@ -212,57 +671,71 @@ pub unsafe fn reflect_attributes(ctxt: &mut CommandContext, program: Handle)
             Handle::Id(program) => {
                 assert!(ctxt.version >= &Version(Api::Gl, 2, 0));
                 ctxt.gl.GetActiveAttrib(program, attribute_id as gl::types::GLuint,
-                                        attr_name_tmp_len, &mut attr_name_tmp_len, &mut data_size,
-                                        &mut data_type, attr_name_tmp.as_mut_ptr()
-                                          as *mut gl::types::GLchar);
+                                        buffer_size, &mut name_len, &mut data_size,
+                                        &mut data_type, buffer);
             },
             Handle::Handle(program) => {
                 assert!(ctxt.extensions.gl_arb_vertex_shader);
                 ctxt.gl.GetActiveAttribARB(program, attribute_id as gl::types::GLuint,
-                                           attr_name_tmp_len, &mut attr_name_tmp_len, &mut data_size,
-                                           &mut data_type, attr_name_tmp.as_mut_ptr()
-                                             as *mut gl::types::GLchar);
+                                           buffer_size, &mut name_len, &mut data_size,
+                                           &mut data_type, buffer);
             }
         };
 
-        attr_name_tmp.set_len(attr_name_tmp_len as usize);
+        let attr_name = try!(names.get(name_len));
+        result.push((attr_name, data_type, data_size));
+    }
+
+    Ok(result)
+}
 
-        let attr_name = String::from_utf8(attr_name_tmp).unwrap();
-        if attr_name.starts_with("gl_") {   // ignoring everything built-in
+/// Reflects the list of active outputs of a program.
+///
+/// Outputs can only be queried with the program interface query API, so an empty list is
+/// returned if it is not supported. Built-in outputs are skipped unless `keep_builtins`
+/// is `true`.
+pub unsafe fn reflect_outputs(ctxt: &mut CommandContext, program: Handle, keep_builtins: bool,
+                              names: &mut NameBuffer) -> Result<ReflectionMap<Output>, String>
+{
+    let mut outputs = HashMap::with_hash_state(Default::default());
+
+    let id = match program_interface_query_id(ctxt, program) {
+        Some(id) => id,
+        None => return Ok(outputs)
+    };
+
+    let list = try!(list_resources_with_type_and_size(ctxt, id, gl::PROGRAM_OUTPUT, names));
+
+    for (name, data_type, data_size) in list.into_iter() {
+        if !keep_builtins && name.starts_with("gl_") {
             continue;
         }
 
-        let location = match program {
-            Handle::Id(program) => {
-                assert!(ctxt.version >= &Version(Api::Gl, 2, 0));
-                ctxt.gl.GetAttribLocation(program,
-                                          ffi::CString::new(attr_name.as_bytes()).unwrap()
-                                            .as_bytes_with_nul().as_ptr() as *const libc::c_char)
-            },
-            Handle::Handle(program) => {
-                assert!(ctxt.extensions.gl_arb_vertex_shader);
-                ctxt.gl.GetAttribLocationARB(program,
-                                             ffi::CString::new(attr_name.as_bytes()).unwrap()
-                                               .as_bytes_with_nul().as_ptr() as *const libc::c_char)
-            }
+        let location = {
+            let name = try!(to_c_string(&name));
+            ctxt.gl.GetProgramResourceLocation(id, gl::PROGRAM_OUTPUT, name.as_ptr())
         };
 
-        attributes.insert(attr_name, Attribute {
+        let component = get_location_component(ctxt, program, gl::PROGRAM_OUTPUT, &name);
+
+        outputs.insert(name, Output {
             location: location,
-            ty: glenum_to_attribute_type(data_type),
+            component: component,
+            ty: try!(glenum_to_attribute_type(data_type)),
             size: data_size as usize,
         });
     }
 
-    attributes
+    Ok(outputs)
 }
 
-pub unsafe fn reflect_uniform_blocks(ctxt: &mut CommandContext, program: Handle)
-                                     -> HashMap<String, UniformBlock, DefaultState<FnvHasher>>
+pub unsafe fn reflect_uniform_blocks(ctxt: &mut CommandContext, program: Handle,
+                                     names: &mut NameBuffer)
+                                     -> Result<ReflectionMap<UniformBlock>, String>
 {
     // uniform blocks are not supported, so there's none
     if ctxt.version < &Version(Api::Gl, 3, 1) {
-        return HashMap::with_hash_state(Default::default());
+        return Ok(HashMap::with_hash_state(Default::default()));
     }
 
     let program = match program {
@@ -272,27 +745,35 @@ pub unsafe fn reflect_uniform_blocks(ctxt: &mut CommandContext, program: Handle)
 
     let mut blocks = HashMap::with_hash_state(Default::default());
 
-    let mut active_blocks: gl::types::GLint = mem::uninitialized();
-    ctxt.gl.GetProgramiv(program, gl::ACTIVE_UNIFORM_BLOCKS, &mut active_blocks);
-
-    let mut active_blocks_max_name_len: gl::types::GLint = mem::uninitialized();
-    ctxt.gl.GetProgramiv(program, gl::ACTIVE_UNIFORM_BLOCK_MAX_NAME_LENGTH,
-                         &mut active_blocks_max_name_len);
-
-    for block_id in (0 .. active_blocks) {
-        // getting the name of the block
-        let name = {
-            let mut name_tmp: Vec<u8> = Vec::with_capacity(1 + active_blocks_max_name_len
-                                                           as usize);
-            let mut name_tmp_len = active_blocks_max_name_len;
-
-            ctxt.gl.GetActiveUniformBlockName(program, block_id as gl::types::GLuint,
-                                              name_tmp_len, &mut name_tmp_len,
-                                              name_tmp.as_mut_ptr() as *mut gl::types::GLchar);
-            name_tmp.set_len(name_tmp_len as usize);
-            String::from_utf8(name_tmp).unwrap()
-        };
+    // names and indices of the blocks
+    let list = match program_interface_query_id(ctxt, Handle::Id(program)) {
+        Some(id) => try!(enumerate_program_resources(ctxt, id, gl::UNIFORM_BLOCK, names)),
+        None => {
+            let mut active_blocks: gl::types::GLint = mem::uninitialized();
+            ctxt.gl.GetProgramiv(program, gl::ACTIVE_UNIFORM_BLOCKS, &mut active_blocks);
+
+            let mut active_blocks_max_name_len: gl::types::GLint = mem::uninitialized();
+            ctxt.gl.GetProgramiv(program, gl::ACTIVE_UNIFORM_BLOCK_MAX_NAME_LENGTH,
+                                 &mut active_blocks_max_name_len);
+
+            let mut list = Vec::with_capacity(active_blocks as usize);
+            for block_id in (0 .. active_blocks as gl::types::GLuint) {
+                let (buffer_size, buffer) = names.prepare(active_blocks_max_name_len);
+                let mut name_len = 0;
+
+                ctxt.gl.GetActiveUniformBlockName(program, block_id, buffer_size,
+                                                  &mut name_len, buffer);
+                list.push((try!(names.get(name_len)), block_id));
+            }
+            list
+        }
+    };
+
+    // first querying the properties of the blocks and the indices of their members
+    let mut blocks_infos = Vec::with_capacity(list.len());
+    let mut all_members_indices: Vec<gl::types::GLuint> = Vec::new();
 
+    for (name, block_id) in list.into_iter() {
         // binding point for this block
         let mut binding: gl::types::GLint = mem::uninitialized();
         ctxt.gl.GetActiveUniformBlockiv(program, block_id as gl::types::GLuint,
@@ -311,91 +792,131 @@ pub unsafe fn reflect_uniform_blocks(ctxt: &mut CommandContext, program: Handle)
         // indices of the members
         let mut members_indices = ::std::iter::repeat(0).take(num_members as usize)
                                                         .collect::<Vec<gl::types::GLuint>>();
-        ctxt.gl.GetActiveUniformBlockiv(program, block_id as gl::types::GLuint,
-                                        gl::UNIFORM_BLOCK_ACTIVE_UNIFORM_INDICES,
-                                        members_indices.as_mut_ptr() as *mut gl::types::GLint);
-
-        // getting the offsets of the members
-        let mut member_offsets = ::std::iter::repeat(0).take(num_members as usize)
-                                                       .collect::<Vec<gl::types::GLint>>();
-        ctxt.gl.GetActiveUniformsiv(program, num_members, members_indices.as_ptr(),
-                                    gl::UNIFORM_OFFSET, member_offsets.as_mut_ptr());
-
-        // getting the types of the members
-        let mut member_types = ::std::iter::repeat(0).take(num_members as usize)
-                                                     .collect::<Vec<gl::types::GLint>>();
-        ctxt.gl.GetActiveUniformsiv(program, num_members, members_indices.as_ptr(),
-                                    gl::UNIFORM_TYPE, member_types.as_mut_ptr());
-
-        // getting the array sizes of the members
-        let mut member_size = ::std::iter::repeat(0).take(num_members as usize)
-                                                    .collect::<Vec<gl::types::GLint>>();
-        ctxt.gl.GetActiveUniformsiv(program, num_members, members_indices.as_ptr(),
-                                    gl::UNIFORM_SIZE, member_size.as_mut_ptr());
-
-        // getting the length of the names of the members
-        let mut member_name_len = ::std::iter::repeat(0).take(num_members as usize)
-                                                         .collect::<Vec<gl::types::GLint>>();
-        ctxt.gl.GetActiveUniformsiv(program, num_members, members_indices.as_ptr(),
-                                    gl::UNIFORM_NAME_LENGTH, member_name_len.as_mut_ptr());
-
-        // getting the names of the members
-        let member_names = member_name_len.iter().zip(members_indices.iter())
-                                          .map(|(&name_len, &index)|
-        {
-            let mut name_tmp: Vec<u8> = Vec::with_capacity(1 + name_len as usize);
-            let mut name_len_tmp = name_len;
-            ctxt.gl.GetActiveUniformName(program, index, name_len, &mut name_len_tmp,
-                                         name_tmp.as_mut_ptr() as *mut gl::types::GLchar);
-            name_tmp.set_len(name_len_tmp as usize);
-
-            String::from_utf8(name_tmp).unwrap()
-        }).collect::<Vec<_>>();
-
-        // now computing the list of members
-        let members = member_names.into_iter().enumerate().map(|(index, name)| {
-            UniformBlockMember {
+        if num_members >= 1 {
+            ctxt.gl.GetActiveUniformBlockiv(program, block_id as gl::types::GLuint,
+                                            gl::UNIFORM_BLOCK_ACTIVE_UNIFORM_INDICES,
+                                            members_indices.as_mut_ptr()
+                                              as *mut gl::types::GLint);
+        }
+
+        let stages = block_stages(ctxt, program, block_id);
+
+        blocks_infos.push((name, binding, block_size, members_indices.len(), stages));
+        all_members_indices.extend(members_indices.into_iter());
+    }
+
+    // then querying the members of all the blocks at once, in order to reduce the number
+    // of calls
+    let member_offsets = get_active_uniforms_iv(ctxt, program, &all_members_indices,
+                                                gl::UNIFORM_OFFSET);
+    let member_array_strides = get_active_uniforms_iv(ctxt, program, &all_members_indices,
+                                                      gl::UNIFORM_ARRAY_STRIDE);
+    let member_matrix_strides = get_active_uniforms_iv(ctxt, program, &all_members_indices,
+                                                       gl::UNIFORM_MATRIX_STRIDE);
+    let member_types = get_active_uniforms_iv(ctxt, program, &all_members_indices,
+                                              gl::UNIFORM_TYPE);
+    let member_size = get_active_uniforms_iv(ctxt, program, &all_members_indices,
+                                             gl::UNIFORM_SIZE);
+    let member_name_len = get_active_uniforms_iv(ctxt, program, &all_members_indices,
+                                                 gl::UNIFORM_NAME_LENGTH);
+
+    // getting the names of the members
+    let mut member_names = Vec::with_capacity(all_members_indices.len());
+    for (&name_len, &index) in member_name_len.iter().zip(all_members_indices.iter()) {
+        let (buffer_size, buffer) = names.prepare(name_len);
+        let mut name_len = 0;
+        ctxt.gl.GetActiveUniformName(program, index, buffer_size, &mut name_len, buffer);
+        member_names.push(try!(names.get(name_len)));
+    }
+
+    // now splitting the members between the blocks
+    let mut member_names = member_names.into_iter().enumerate();
+
+    for (name, binding, block_size, num_members, stages) in blocks_infos.into_iter() {
+        let mut members = Vec::with_capacity(num_members);
+
+        for (index, name) in member_names.by_ref().take(num_members) {
+            members.push(UniformBlockMember {
                 name: name,
                 offset: member_offsets[index] as usize,
-                ty: glenum_to_uniform_type(member_types[index] as gl::types::GLenum),
+                array_stride: member_array_strides[index] as usize,
+                matrix_stride: member_matrix_strides[index] as usize,
+                ty: try!(glenum_to_uniform_type(member_types[index] as gl::types::GLenum)),
                 size: match member_size[index] {
                     1 => None,
                     a => Some(a as usize),
                 },
-            }
-        }).collect::<Vec<_>>();
+            });
+        }
 
         // finally inserting into the blocks list
         blocks.insert(name, UniformBlock {
             binding: binding as i32,
             size: block_size as usize,
             members: members,
+            stages: stages,
         });
     }
 
-    blocks
+    Ok(blocks)
 }
 
-pub unsafe fn reflect_transform_feedback(ctxt: &mut CommandContext, program: Handle)
-                                         -> Option<(Vec<TransformFeedbackVarying>,
-                                                    TransformFeedbackMode)>
+/// Queries which stages of a program reference a uniform block.
+///
+/// Stages that are not supported by the context are never part of the result.
+unsafe fn block_stages(ctxt: &mut CommandContext, program: gl::types::GLuint,
+                       block_id: gl::types::GLuint) -> StageMask
+{
+    let geometry = ctxt.version >= &Version(Api::Gl, 3, 2) ||
+                   ctxt.version >= &Version(Api::GlEs, 3, 2);
+    let tessellation = ctxt.version >= &Version(Api::Gl, 4, 0) ||
+                       ctxt.version >= &Version(Api::GlEs, 3, 2);
+    let compute = ctxt.version >= &Version(Api::Gl, 4, 3) ||
+                  ctxt.version >= &Version(Api::GlEs, 3, 1);
+
+    let mut query = |pname| {
+        let mut value: gl::types::GLint = 0;
+        ctxt.gl.GetActiveUniformBlockiv(program, block_id, pname, &mut value);
+        value != 0
+    };
+
+    StageMask {
+        vertex: query(gl::UNIFORM_BLOCK_REFERENCED_BY_VERTEX_SHADER),
+        tessellation_control: tessellation &&
+                              query(gl::UNIFORM_BLOCK_REFERENCED_BY_TESS_CONTROL_SHADER),
+        tessellation_evaluation: tessellation &&
+                                 query(gl::UNIFORM_BLOCK_REFERENCED_BY_TESS_EVALUATION_SHADER),
+        geometry: geometry && query(gl::UNIFORM_BLOCK_REFERENCED_BY_GEOMETRY_SHADER),
+        fragment: query(gl::UNIFORM_BLOCK_REFERENCED_BY_FRAGMENT_SHADER),
+        compute: compute && query(gl::UNIFORM_BLOCK_REFERENCED_BY_COMPUTE_SHADER),
+    }
+}
+
+pub unsafe fn reflect_transform_feedback(ctxt: &mut CommandContext, program: Handle,
+                                         names: &mut NameBuffer)
+                                         -> Result<Option<(Vec<TransformFeedbackVarying>,
+                                                           TransformFeedbackMode)>, String>
 {
     let program = match program {
         // transform feedback not supported
-        Handle::Handle(_) => return None,
+        Handle::Handle(_) => return Ok(None),
         Handle::Id(id) => id
     };
 
+    // transform feedback is core in OpenGL 3.0 and OpenGL ES 3.0
+    let core = ctxt.version >= &Version(Api::Gl, 3, 0) ||
+               ctxt.version >= &Version(Api::GlEs, 3, 0);
+
     // transform feedback not supported
-    if ctxt.version < &Version(Api::Gl, 3, 0) && !ctxt.extensions.gl_ext_transform_feedback {
-        return None;
+    if !core && !ctxt.extensions.gl_ext_transform_feedback {
+        return Ok(None);
     }
 
     // querying the number of varying
     let num_varyings = {
         let mut num_varyings: gl::types::GLint = mem::uninitialized();
 
-        if ctxt.version >= &Version(Api::Gl, 3, 0) {
+        if core {
             ctxt.gl.GetProgramiv(program, gl::TRANSFORM_FEEDBACK_VARYINGS, &mut num_varyings);
         } else if ctxt.extensions.gl_ext_transform_feedback {
             ctxt.gl.GetProgramiv(program, gl::TRANSFORM_FEEDBACK_VARYINGS_EXT, &mut num_varyings);
@@ -408,14 +929,14 @@ pub unsafe fn reflect_transform_feedback(ctxt: &mut CommandContext, program: Han
 
     // no need to request other things if there are no varying
     if num_varyings == 0 {
-        return None;
+        return Ok(None);
     }
 
     // querying "interleaved" or "separate"
     let buffer_mode = {
         let mut buffer_mode: gl::types::GLint = mem::uninitialized();
 
-        if ctxt.version >= &Version(Api::Gl, 3, 0) {
+        if core {
             ctxt.gl.GetProgramiv(program, gl::TRANSFORM_FEEDBACK_BUFFER_MODE, &mut buffer_mode);
         } else if ctxt.extensions.gl_ext_transform_feedback {
             ctxt.gl.GetProgramiv(program, gl::TRANSFORM_FEEDBACK_BUFFER_MODE_EXT, &mut buffer_mode);
@@ -423,12 +944,12 @@ pub unsafe fn reflect_transform_feedback(ctxt: &mut CommandContext, program: Han
             unreachable!();
         }
 
-        glenum_to_transform_feedback_mode(buffer_mode as gl::types::GLenum)
+        try!(glenum_to_transform_feedback_mode(buffer_mode as gl::types::GLenum))
     };
 
     // the max length includes the null terminator
     let mut max_buffer_len: gl::types::GLint = mem::uninitialized();
-    if ctxt.version >= &Version(Api::Gl, 3, 0) {
+    if core {
         ctxt.gl.GetProgramiv(program, gl::TRANSFORM_FEEDBACK_VARYING_MAX_LENGTH,
                              &mut max_buffer_len);
     } else if ctxt.extensions.gl_ext_transform_feedback {
@@ -439,42 +960,146 @@ pub unsafe fn reflect_transform_feedback(ctxt: &mut CommandContext, program: Han
     }
 
     let mut result = Vec::with_capacity(num_varyings as usize);
+    let mut offset = 0;
 
     for index in (0 .. num_varyings as gl::types::GLuint) {
-        let mut name_tmp: Vec<u8> = Vec::with_capacity(max_buffer_len as usize);
-        let mut name_tmp_len = max_buffer_len;
+        let (buffer_size, buffer) = names.prepare(max_buffer_len);
+        let mut name_len = 0;
 
         let mut size = mem::uninitialized();
         let mut ty = mem::uninitialized();
 
-        if ctxt.version >= &Version(Api::Gl, 3, 0) {
-            ctxt.gl.GetTransformFeedbackVarying(program, index, name_tmp_len, &mut name_tmp_len,
-                                                &mut size, &mut ty, name_tmp.as_mut_ptr()
-                                                as *mut gl::types::GLchar);
+        if core {
+            ctxt.gl.GetTransformFeedbackVarying(program, index, buffer_size, &mut name_len,
+                                                &mut size, &mut ty, buffer);
         } else if ctxt.extensions.gl_ext_transform_feedback {
-            ctxt.gl.GetTransformFeedbackVaryingEXT(program, index, name_tmp_len,
-                                                   &mut name_tmp_len, &mut size, &mut ty,
-                                                   name_tmp.as_mut_ptr()
-                                                   as *mut gl::types::GLchar);
+            ctxt.gl.GetTransformFeedbackVaryingEXT(program, index, buffer_size, &mut name_len,
+                                                   &mut size, &mut ty, buffer);
         } else {
             unreachable!();
         }
 
-        name_tmp.set_len(name_tmp_len as usize);
-        let name = String::from_utf8(name_tmp).unwrap();
+        let name = try!(names.get(name_len));
+
+        // `size` is the number of elements of the array
+        let ty = try!(glenum_to_attribute_type(ty as gl::types::GLenum));
+        let size_bytes = size as usize * ty.get_size_bytes();
 
         result.push(TransformFeedbackVarying {
             name: name,
-            size: size as usize,
-            ty: glenum_to_attribute_type(ty as gl::types::GLenum),
+            size: size_bytes,
+            offset: match buffer_mode {
+                TransformFeedbackMode::Interleaved => offset,
+                TransformFeedbackMode::Separate => 0,
+            },
+            num_components: size as usize * ty.get_num_components(),
+            ty: ty,
         });
+
+        offset += size_bytes;
     }
 
-    Some((result, buffer_mode))
+    Ok(Some((result, buffer_mode)))
 }
 
-fn glenum_to_uniform_type(ty: gl::types::GLenum) -> UniformType {
-    match ty {
+/// Returns the id of the program if the program interface query API can be used with it.
+pub fn program_interface_query_id(ctxt: &CommandContext, program: Handle)
+                              -> Option<gl::types::GLuint>
+{
+    match program {
+        Handle::Id(id) if ctxt.version >= &Version(Api::Gl, 4, 3) ||
+                          ctxt.extensions.gl_arb_program_interface_query => Some(id),
+        _ => None
+    }
+}
+
+/// Returns the `GL_LOCATION_COMPONENT` of a resource of a program.
+///
+/// Returns `None` if OpenGL 4.4 or `GL_ARB_enhanced_layouts` is not supported.
+unsafe fn get_location_component(ctxt: &mut CommandContext, program: Handle,
+                                 interface: gl::types::GLenum, name: &str) -> Option<u32>
+{
+    let id = match program_interface_query_id(ctxt, program) {
+        Some(id) => id,
+        None => return None
+    };
+
+    if !(ctxt.version >= &Version(Api::Gl, 4, 4)) && !ctxt.extensions.gl_arb_enhanced_layouts {
+        return None;
+    }
+
+    let name = ffi::CString::new(name.as_bytes()).unwrap();
+    let index = ctxt.gl.GetProgramResourceIndex(id, interface, name.as_ptr());
+    if index == gl::INVALID_INDEX {
+        return None;
+    }
+
+    let mut value: gl::types::GLint = 0;
+    ctxt.gl.GetProgramResourceiv(id, interface, index, 1, &gl::LOCATION_COMPONENT, 1,
+                                 ptr::null_mut(), &mut value);
+    Some(value as u32)
+}
+
+/// Returns the name and index of each active resource of an interface of a program.
+///
+/// `interface` must be an interface whose resources have a name, like `GL_UNIFORM`,
+/// `GL_PROGRAM_INPUT`, `GL_PROGRAM_OUTPUT`, `GL_UNIFORM_BLOCK` or `GL_SHADER_STORAGE_BLOCK`.
+/// Requires OpenGL 4.3 or `GL_ARB_program_interface_query`.
+pub unsafe fn enumerate_program_resources(ctxt: &mut CommandContext, program: gl::types::GLuint,
+                                          interface: gl::types::GLenum, names: &mut NameBuffer)
+                                          -> Result<Vec<(String, gl::types::GLuint)>, String>
+{
+    let mut active_resources: gl::types::GLint = 0;
+    ctxt.gl.GetProgramInterfaceiv(program, interface, gl::ACTIVE_RESOURCES,
+                                  &mut active_resources);
+
+    // the max length includes the null terminator
+    let mut max_name_len: gl::types::GLint = 0;
+    ctxt.gl.GetProgramInterfaceiv(program, interface, gl::MAX_NAME_LENGTH, &mut max_name_len);
+
+    let mut result = Vec::with_capacity(active_resources as usize);
+
+    for index in (0 .. active_resources as gl::types::GLuint) {
+        let (buffer_size, buffer) = names.prepare(max_name_len);
+        let mut name_len = 0;
+
+        ctxt.gl.GetProgramResourceName(program, interface, index, buffer_size, &mut name_len,
+                                       buffer);
+        result.push((try!(names.get(name_len)), index));
+    }
+
+    Ok(result)
+}
+
+/// Same as `enumerate_program_resources`, but also returns the type and array size of each
+/// resource.
+unsafe fn list_resources_with_type_and_size(ctxt: &mut CommandContext,
+                                            program: gl::types::GLuint,
+                                            interface: gl::types::GLenum,
+                                            names: &mut NameBuffer)
+                                            -> Result<Vec<(String, gl::types::GLenum,
+                                                           gl::types::GLint)>, String>
+{
+    let resources = try!(enumerate_program_resources(ctxt, program, interface, names));
+    let properties = [gl::TYPE, gl::ARRAY_SIZE];
+
+    Ok(resources.into_iter().map(|(name, index)| {
+        let mut values: [gl::types::GLint; 2] = [0, 0];
+        ctxt.gl.GetProgramResourceiv(program, interface, index, 2, properties.as_ptr(), 2,
+                                     ptr::null_mut(), values.as_mut_ptr());
+        (name, values[0] as gl::types::GLenum, values[1])
+    }).collect())
+}
+
+/// Turns the name of a resource into a string that can be passed to OpenGL.
+fn to_c_string(name: &str) -> Result<ffi::CString, String> {
+    ffi::CString::new(name.as_bytes()).map_err(|_| {
+        format!("OpenGL returned a name that contains a null byte: {:?}", name)
+    })
+}
+
+fn glenum_to_uniform_type(ty: gl::types::GLenum) -> Result<UniformType, String> {
+    Ok(match ty {
         gl::FLOAT => UniformType::Float,
         gl::FLOAT_VEC2 => UniformType::FloatVec2,
         gl::FLOAT_VEC3 => UniformType::FloatVec3,
@@ -526,6 +1151,8 @@ fn glenum_to_uniform_type(ty: gl::types::GLenum) -> UniformType {
         gl::SAMPLER_2D_MULTISAMPLE => UniformType::Sampler2dMultisample,
         gl::SAMPLER_2D_MULTISAMPLE_ARRAY => UniformType::Sampler2dMultisampleArray,
         gl::SAMPLER_CUBE_SHADOW => UniformType::SamplerCubeShadow,
+        gl::SAMPLER_CUBE_MAP_ARRAY => UniformType::SamplerCubeArray,
+        gl::SAMPLER_CUBE_MAP_ARRAY_SHADOW => UniformType::SamplerCubeArrayShadow,
         gl::SAMPLER_BUFFER => UniformType::SamplerBuffer,
         gl::SAMPLER_2D_RECT => UniformType::Sampler2dRect,
         gl::SAMPLER_2D_RECT_SHADOW => UniformType::Sampler2dRectShadow,
@@ -533,6 +1160,7 @@ fn glenum_to_uniform_type(ty: gl::types::GLenum) -> UniformType {
         gl::INT_SAMPLER_2D => UniformType::ISampler2d,
         gl::INT_SAMPLER_3D => UniformType::ISampler3d,
         gl::INT_SAMPLER_CUBE => UniformType::ISamplerCube,
+        gl::INT_SAMPLER_CUBE_MAP_ARRAY => UniformType::ISamplerCubeArray,
         gl::INT_SAMPLER_1D_ARRAY => UniformType::ISampler1dArray,
         gl::INT_SAMPLER_2D_ARRAY => UniformType::ISampler2dArray,
         gl::INT_SAMPLER_2D_MULTISAMPLE => UniformType::ISampler2dMultisample,
@@ -543,7 +1171,8 @@ fn glenum_to_uniform_type(ty: gl::types::GLenum) -> UniformType {
         gl::UNSIGNED_INT_SAMPLER_2D => UniformType::USampler2d,
         gl::UNSIGNED_INT_SAMPLER_3D => UniformType::USampler3d,
         gl::UNSIGNED_INT_SAMPLER_CUBE => UniformType::USamplerCube,
-        gl::UNSIGNED_INT_SAMPLER_1D_ARRAY => UniformType::USampler2dArray,
+        gl::UNSIGNED_INT_SAMPLER_CUBE_MAP_ARRAY => UniformType::USamplerCubeArray,
+        gl::UNSIGNED_INT_SAMPLER_1D_ARRAY => UniformType::USampler1dArray,
         gl::UNSIGNED_INT_SAMPLER_2D_ARRAY => UniformType::USampler2dArray,
         gl::UNSIGNED_INT_SAMPLER_2D_MULTISAMPLE => UniformType::USampler2dMultisample,
         gl::UNSIGNED_INT_SAMPLER_2D_MULTISAMPLE_ARRAY => UniformType::USampler2dMultisampleArray,
@@ -580,12 +1209,107 @@ fn glenum_to_uniform_type(ty: gl::types::GLenum) -> UniformType {
         gl::UNSIGNED_INT_IMAGE_2D_MULTISAMPLE => UniformType::UImage2dMultisample,
         gl::UNSIGNED_INT_IMAGE_2D_MULTISAMPLE_ARRAY => UniformType::UImage2dMultisampleArray,
         gl::UNSIGNED_INT_ATOMIC_COUNTER => UniformType::AtomicCounterUint,
-        v => panic!("Unknown value returned by OpenGL uniform type: {}", v)
+        v => return Err(format!("Unknown value returned by OpenGL uniform type: {}", v))
+    })
+}
+
+/// Returns true if the uniform type is a sampler or an image.
+fn is_sampler_or_image(ty: UniformType) -> bool {
+    match ty {
+        UniformType::Float | UniformType::FloatVec2 | UniformType::FloatVec3 |
+        UniformType::FloatVec4 | UniformType::Double | UniformType::DoubleVec2 |
+        UniformType::DoubleVec3 | UniformType::DoubleVec4 | UniformType::Int |
+        UniformType::IntVec2 | UniformType::IntVec3 | UniformType::IntVec4 |
+        UniformType::UnsignedInt | UniformType::UnsignedIntVec2 |
+        UniformType::UnsignedIntVec3 | UniformType::UnsignedIntVec4 | UniformType::Bool |
+        UniformType::BoolVec2 | UniformType::BoolVec3 | UniformType::BoolVec4 |
+        UniformType::FloatMat2 | UniformType::FloatMat3 | UniformType::FloatMat4 |
+        UniformType::FloatMat2x3 | UniformType::FloatMat2x4 | UniformType::FloatMat3x2 |
+        UniformType::FloatMat3x4 | UniformType::FloatMat4x2 | UniformType::FloatMat4x3 |
+        UniformType::DoubleMat2 | UniformType::DoubleMat3 | UniformType::DoubleMat4 |
+        UniformType::DoubleMat2x3 | UniformType::DoubleMat2x4 | UniformType::DoubleMat3x2 |
+        UniformType::DoubleMat3x4 | UniformType::DoubleMat4x2 | UniformType::DoubleMat4x3 |
+        UniformType::AtomicCounterUint => false,
+        _ => true,
+    }
+}
+
+/// Queries a property of multiple uniforms with a single call to `glGetActiveUniformsiv`.
+unsafe fn get_active_uniforms_iv(ctxt: &mut CommandContext, program: gl::types::GLuint,
+                                 indices: &[gl::types::GLuint], pname: gl::types::GLenum)
+                                 -> Vec<gl::types::GLint>
+{
+    let mut result = ::std::iter::repeat(0).take(indices.len())
+                                           .collect::<Vec<gl::types::GLint>>();
+
+    if !indices.is_empty() {
+        ctxt.gl.GetActiveUniformsiv(program, indices.len() as gl::types::GLsizei,
+                                    indices.as_ptr(), pname, result.as_mut_ptr());
     }
+
+    result
 }
 
-fn glenum_to_attribute_type(value: gl::types::GLenum) -> AttributeType {
-    match value {
+/// Returns true if the uniform is an image that must be bound with `glBindImageTexture`.
+pub fn is_image(ty: UniformType) -> bool {
+    match ty {
+        UniformType::Image1d | UniformType::IImage1d | UniformType::UImage1d |
+        UniformType::Image2d | UniformType::IImage2d | UniformType::UImage2d |
+        UniformType::Image3d | UniformType::IImage3d | UniformType::UImage3d |
+        UniformType::Image2dRect | UniformType::IImage2dRect | UniformType::UImage2dRect |
+        UniformType::ImageCube | UniformType::IImageCube | UniformType::UImageCube |
+        UniformType::ImageBuffer | UniformType::IImageBuffer | UniformType::UImageBuffer |
+        UniformType::Image1dArray | UniformType::IImage1dArray | UniformType::UImage1dArray |
+        UniformType::Image2dArray | UniformType::IImage2dArray | UniformType::UImage2dArray |
+        UniformType::Image2dMultisample | UniformType::IImage2dMultisample |
+        UniformType::UImage2dMultisample | UniformType::Image2dMultisampleArray |
+        UniformType::IImage2dMultisampleArray | UniformType::UImage2dMultisampleArray => true,
+        _ => false,
+    }
+}
+
+/// Returns the kind of texture that a uniform reads from, or `None` if it is not a sampler.
+pub fn sampler_kind(ty: UniformType) -> Option<SamplerKind> {
+    match ty {
+        UniformType::Sampler1d | UniformType::ISampler1d | UniformType::USampler1d => {
+            Some(SamplerKind::Texture1d)
+        },
+        UniformType::Sampler2d | UniformType::ISampler2d | UniformType::USampler2d => {
+            Some(SamplerKind::Texture2d)
+        },
+        UniformType::Sampler3d | UniformType::ISampler3d | UniformType::USampler3d => {
+            Some(SamplerKind::Texture3d)
+        },
+        UniformType::Sampler1dArray | UniformType::ISampler1dArray |
+        UniformType::USampler1dArray => Some(SamplerKind::Texture1dArray),
+        UniformType::Sampler2dArray | UniformType::ISampler2dArray |
+        UniformType::USampler2dArray => Some(SamplerKind::Texture2dArray),
+        UniformType::SamplerCube | UniformType::ISamplerCube | UniformType::USamplerCube => {
+            Some(SamplerKind::Cubemap)
+        },
+        UniformType::SamplerCubeArray | UniformType::ISamplerCubeArray |
+        UniformType::USamplerCubeArray => Some(SamplerKind::CubemapArray),
+        UniformType::Sampler2dRect | UniformType::ISampler2dRect |
+        UniformType::USampler2dRect => Some(SamplerKind::Texture2dRect),
+        UniformType::SamplerBuffer | UniformType::ISamplerBuffer |
+        UniformType::USamplerBuffer => Some(SamplerKind::Buffer),
+        UniformType::Sampler2dMultisample | UniformType::ISampler2dMultisample |
+        UniformType::USampler2dMultisample => Some(SamplerKind::Texture2dMultisample),
+        UniformType::Sampler2dMultisampleArray | UniformType::ISampler2dMultisampleArray |
+        UniformType::USampler2dMultisampleArray => Some(SamplerKind::Texture2dMultisampleArray),
+        UniformType::Sampler1dShadow => Some(SamplerKind::Shadow1d),
+        UniformType::Sampler2dShadow => Some(SamplerKind::Shadow2d),
+        UniformType::SamplerCubeShadow => Some(SamplerKind::ShadowCubemap),
+        UniformType::Sampler1dArrayShadow => Some(SamplerKind::Shadow1dArray),
+        UniformType::Sampler2dArrayShadow => Some(SamplerKind::Shadow2dArray),
+        UniformType::SamplerCubeArrayShadow => Some(SamplerKind::ShadowCubemapArray),
+        UniformType::Sampler2dRectShadow => Some(SamplerKind::Shadow2dRect),
+        _ => None,
+    }
+}
+
+fn glenum_to_attribute_type(value: gl::types::GLenum) -> Result<AttributeType, String> {
+    Ok(match value {
         gl::FLOAT => AttributeType::F32,
         gl::FLOAT_VEC2 => AttributeType::F32F32,
         gl::FLOAT_VEC3 => AttributeType::F32F32F32,
@@ -610,18 +1334,38 @@ fn glenum_to_attribute_type(value: gl::types::GLenum) -> AttributeType {
         gl::FLOAT_MAT3x4 => AttributeType::F32x3x4,
         gl::FLOAT_MAT4x2 => AttributeType::F32x4x2,
         gl::FLOAT_MAT4x3 => AttributeType::F32x4x3,
-        v => panic!("Unknown value returned by OpenGL attribute type: {}", v)
-    }
+        gl::DOUBLE => AttributeType::F64,
+        gl::DOUBLE_VEC2 => AttributeType::F64F64,
+        gl::DOUBLE_VEC3 => AttributeType::F64F64F64,
+        gl::DOUBLE_VEC4 => AttributeType::F64F64F64F64,
+        gl::DOUBLE_MAT2 => AttributeType::F64x2x2,
+        gl::DOUBLE_MAT3 => AttributeType::F64x3x3,
+        gl::DOUBLE_MAT4 => AttributeType::F64x4x4,
+        gl::DOUBLE_MAT2x3 => AttributeType::F64x2x3,
+        gl::DOUBLE_MAT2x4 => AttributeType::F64x2x4,
+        gl::DOUBLE_MAT3x2 => AttributeType::F64x3x2,
+        gl::DOUBLE_MAT3x4 => AttributeType::F64x3x4,
+        gl::DOUBLE_MAT4x2 => AttributeType::F64x4x2,
+        gl::DOUBLE_MAT4x3 => AttributeType::F64x4x3,
+        // OpenGL stores booleans as 32bits integers
+        gl::BOOL => AttributeType::I32,
+        gl::BOOL_VEC2 => AttributeType::I32I32,
+        gl::BOOL_VEC3 => AttributeType::I32I32I32,
+        gl::BOOL_VEC4 => AttributeType::I32I32I32I32,
+        v => return Err(format!("Unknown value returned by OpenGL attribute type: {}", v))
+    })
 }
 
-fn glenum_to_transform_feedback_mode(value: gl::types::GLenum) -> TransformFeedbackMode {
-    match value {
+fn glenum_to_transform_feedback_mode(value: gl::types::GLenum)
+                                     -> Result<TransformFeedbackMode, String>
+{
+    Ok(match value {
         gl::INTERLEAVED_ATTRIBS/* | gl::INTERLEAVED_ATTRIBS_EXT*/ => {
             TransformFeedbackMode::Interleaved
         },
         gl::SEPARATE_ATTRIBS/* | gl::SEPARATE_ATTRIBS_EXT*/ => {
             TransformFeedbackMode::Separate
         },
-        v => panic!("Unknown value returned by OpenGL varying mode: {}", v)
-    }
+        v => return Err(format!("Unknown value returned by OpenGL varying mode: {}", v))
+    })
 }
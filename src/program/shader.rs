@@ -1,4 +1,6 @@
 use gl;
+use libc;
+
 use context::CommandContext;
 use version::Version;
 use version::Api;
@@ -12,14 +14,58 @@ use std::rc::Rc;
 
 use GlObject;
 use Handle;
+use ToGlEnum;
 
 use program::COMPILER_GLOBAL_LOCK;
-use program::ProgramCreationError;
+use program::{ProgramCreationError, ShaderError};
+use program::{ShaderStage, SpecConstantValue};
 
 /// A single, compiled but unlinked, shader.
+///
+/// A `Shader` can be linked with other shaders with `Program::from_shaders`. The same shader
+/// can be used in multiple programs.
 pub struct Shader {
     context: Rc<Context>,
     id: Handle,
+    shader_type: gl::types::GLenum,
+}
+
+impl Shader {
+    /// Compiles a shader from GLSL source code.
+    pub fn new<F>(facade: &F, stage: ShaderStage, source_code: &str)
+                  -> Result<Shader, ProgramCreationError> where F: Facade
+    {
+        build_shader(facade, stage.to_glenum(), source_code)
+    }
+
+    /// Returns the info log generated by the compiler.
+    ///
+    /// Even when compilation succeeds, the log can contain warnings or performance hints
+    /// depending on the driver. The content of the log is implementation-defined.
+    pub fn get_info_log(&self) -> String {
+        let mut ctxt = self.context.make_current();
+        unsafe { get_shader_info_log(&mut ctxt, self.id) }
+    }
+
+    /// Returns the stage of the pipeline that this shader is executed in.
+    pub fn get_stage(&self) -> ShaderStage {
+        glenum_to_shader_stage(self.shader_type).unwrap()
+    }
+}
+
+/// Returns the stage corresponding to a type of shader, or `None` for compute shaders.
+pub fn glenum_to_shader_stage(shader_type: gl::types::GLenum) -> Option<ShaderStage> {
+    match shader_type {
+        gl::VERTEX_SHADER => Some(ShaderStage::Vertex),
+        gl::TESS_CONTROL_SHADER => Some(ShaderStage::TessellationControl),
+        gl::TESS_EVALUATION_SHADER => Some(ShaderStage::TessellationEvaluation),
+        gl::GEOMETRY_SHADER => Some(ShaderStage::Geometry),
+        gl::FRAGMENT_SHADER => Some(ShaderStage::Fragment),
+        gl::MESH_SHADER_NV => Some(ShaderStage::Mesh),
+        gl::TASK_SHADER_NV => Some(ShaderStage::Task),
+        gl::COMPUTE_SHADER => None,
+        _ => unreachable!()
+    }
 }
 
 impl GlObject for Shader {
@@ -49,6 +95,18 @@ impl Drop for Shader {
     }
 }
 
+/// Removes the UTF-8 byte order mark that some editors write at the start of files.
+///
+/// Drivers don't expect it and usually reject the `#version` directive that follows it,
+/// because it is no longer the first token of the source code.
+pub fn strip_bom(source: &str) -> &str {
+    if source.starts_with("\u{feff}") {
+        &source["\u{feff}".len() ..]
+    } else {
+        source
+    }
+}
+
 /// Builds an individual shader.
 pub fn build_shader<F>(facade: &F, shader_type: gl::types::GLenum, source_code: &str)
                        -> Result<Shader, ProgramCreationError> where F: Facade
@@ -62,7 +120,8 @@ pub fn build_shader<F>(facade: &F, shader_type: gl::types::GLenum, source_code:
 
         try!(check_shader_type_compatibility(&mut ctxt, shader_type));
 
-        let source_code = ffi::CString::new(source_code.as_bytes()).unwrap();
+        let source = strip_bom(source_code);
+        let source_code = ffi::CString::new(source.as_bytes()).unwrap();
 
         let id = if ctxt.version >= &Version(Api::Gl, 2, 0) ||
                     ctxt.version >= &Version(Api::GlEs, 2, 0)
@@ -132,53 +191,296 @@ pub fn build_shader<F>(facade: &F, shader_type: gl::types::GLenum, source_code:
         if compilation_success == 1 {
             Ok(Shader {
                 context: facade.get_context().clone(),
-                id: id
+                id: id,
+                shader_type: shader_type,
             })
 
         } else {
-            // compilation error
-            let mut error_log_size: gl::types::GLint = mem::uninitialized();
+            let log = get_shader_info_log(&mut ctxt, id);
+            Err(ProgramCreationError::CompilationError(ShaderError {
+                stage: glenum_to_shader_stage(shader_type),
+                log: annotate_log(&log, source),
+            }))
+        }
+    }
+}
 
-            match id {
-                Handle::Id(id) => {
-                    assert!(ctxt.version >= &Version(Api::Gl, 2, 0) ||
-                            ctxt.version >= &Version(Api::GlEs, 2, 0));
-                    ctxt.gl.GetShaderiv(id, gl::INFO_LOG_LENGTH, &mut error_log_size);
-                },
-                Handle::Handle(id) => {
-                    assert!(ctxt.extensions.gl_arb_shader_objects);
-                    ctxt.gl.GetObjectParameterivARB(id, gl::OBJECT_INFO_LOG_LENGTH_ARB,
-                                                    &mut error_log_size);
-                }
+/// Builds an individual shader from a SPIR-V module.
+///
+/// The module is uploaded with `glShaderBinary`, then specialized with the given entry point
+/// and specialization constants.
+pub fn build_spirv_shader<F>(facade: &F, shader_type: gl::types::GLenum, binary: &[u8],
+                             entry_point: &str, constants: &[(u32, SpecConstantValue)])
+                             -> Result<Shader, ProgramCreationError> where F: Facade
+{
+    unsafe {
+        let mut ctxt = facade.get_context().make_current();
+
+        if !(ctxt.version >= &Version(Api::Gl, 4, 6)) && !ctxt.extensions.gl_arb_gl_spirv {
+            return Err(ProgramCreationError::CompilationNotSupported);
+        }
+
+        try!(check_shader_type_compatibility(&mut ctxt, shader_type));
+
+        let entry_point = ffi::CString::new(entry_point.as_bytes()).unwrap();
+
+        let constants_indices = constants.iter().map(|&(index, _)| index)
+                                         .collect::<Vec<gl::types::GLuint>>();
+        let constants_values = constants.iter().map(|&(_, ref value)| value.to_raw())
+                                        .collect::<Vec<gl::types::GLuint>>();
+        let num_constants = constants.len() as gl::types::GLuint;
+
+        let id = ctxt.gl.CreateShader(shader_type);
+        if id == 0 {
+            return Err(ProgramCreationError::ShaderTypeNotSupported);
+        }
+
+        ctxt.gl.ShaderBinary(1, &id, gl::SHADER_BINARY_FORMAT_SPIR_V_ARB,
+                             binary.as_ptr() as *const libc::c_void,
+                             binary.len() as gl::types::GLsizei);
+
+        // specializing
+        {
+            let _lock = COMPILER_GLOBAL_LOCK.lock();
+
+            ctxt.report_debug_output_errors.set(false);
+
+            if ctxt.version >= &Version(Api::Gl, 4, 6) {
+                ctxt.gl.SpecializeShader(id, entry_point.as_ptr(), num_constants,
+                                         constants_indices.as_ptr(), constants_values.as_ptr());
+            } else if ctxt.extensions.gl_arb_gl_spirv {
+                ctxt.gl.SpecializeShaderARB(id, entry_point.as_ptr(), num_constants,
+                                            constants_indices.as_ptr(),
+                                            constants_values.as_ptr());
+            } else {
+                unreachable!();
             }
 
-            let mut error_log: Vec<u8> = Vec::with_capacity(error_log_size as usize);
+            ctxt.report_debug_output_errors.set(true);
+        }
 
-            match id {
-                Handle::Id(id) => {
-                    assert!(ctxt.version >= &Version(Api::Gl, 2, 0) ||
-                            ctxt.version >= &Version(Api::GlEs, 2, 0));
-                    ctxt.gl.GetShaderInfoLog(id, error_log_size, &mut error_log_size,
-                                             error_log.as_mut_ptr() as *mut gl::types::GLchar);
-                },
-                Handle::Handle(id) => {
-                    assert!(ctxt.extensions.gl_arb_shader_objects);
-                    ctxt.gl.GetInfoLogARB(id, error_log_size, &mut error_log_size,
-                                          error_log.as_mut_ptr() as *mut gl::types::GLchar);
-                }
+        // specialization errors are reported through the compile status
+        let mut compilation_success: gl::types::GLint = mem::uninitialized();
+        ctxt.gl.GetShaderiv(id, gl::COMPILE_STATUS, &mut compilation_success);
+
+        if compilation_success == 1 {
+            Ok(Shader {
+                context: facade.get_context().clone(),
+                id: Handle::Id(id),
+                shader_type: shader_type,
+            })
+
+        } else {
+            let log = get_shader_info_log(&mut ctxt, Handle::Id(id));
+            ctxt.gl.DeleteShader(id);
+            Err(ProgramCreationError::CompilationError(ShaderError {
+                stage: glenum_to_shader_stage(shader_type),
+                log: log,
+            }))
+        }
+    }
+}
+
+/// Builds an individual shader from a vendor-specific binary.
+///
+/// The binary is uploaded with `glShaderBinary`. Returns `CompilationNotSupported` if the
+/// backend doesn't accept the format.
+pub fn build_binary_shader<F>(facade: &F, shader_type: gl::types::GLenum,
+                              format: gl::types::GLenum, binary: &[u8])
+                              -> Result<Shader, ProgramCreationError> where F: Facade
+{
+    unsafe {
+        let mut ctxt = facade.get_context().make_current();
+
+        if !ctxt.capabilities.shader_binary_formats.contains(&format) {
+            return Err(ProgramCreationError::CompilationNotSupported);
+        }
+
+        try!(check_shader_type_compatibility(&mut ctxt, shader_type));
+
+        let id = ctxt.gl.CreateShader(shader_type);
+        if id == 0 {
+            return Err(ProgramCreationError::ShaderTypeNotSupported);
+        }
+
+        // an invalid binary is reported with an `INVALID_VALUE` error, so the errors that were
+        // generated by previous commands must be cleared first ; the number of iterations is
+        // bounded in case the context is lost
+        for _ in (0 .. 16) {
+            if ctxt.gl.GetError() == gl::NO_ERROR {
+                break;
             }
+        }
+
+        let binary_error = {
+            let _lock = COMPILER_GLOBAL_LOCK.lock();
+
+            ctxt.report_debug_output_errors.set(false);
+            ctxt.gl.ShaderBinary(1, &id, format, binary.as_ptr() as *const libc::c_void,
+                                 binary.len() as gl::types::GLsizei);
+            ctxt.report_debug_output_errors.set(true);
+
+            ctxt.gl.GetError()
+        };
+
+        // some drivers accept the binary and report the problem through the compile status
+        let mut compilation_success: gl::types::GLint = mem::uninitialized();
+        ctxt.gl.GetShaderiv(id, gl::COMPILE_STATUS, &mut compilation_success);
+
+        if binary_error == gl::NO_ERROR && compilation_success == 1 {
+            Ok(Shader {
+                context: facade.get_context().clone(),
+                id: Handle::Id(id),
+                shader_type: shader_type,
+            })
+
+        } else {
+            let log = get_shader_info_log(&mut ctxt, Handle::Id(id));
+            ctxt.gl.DeleteShader(id);
+            Err(ProgramCreationError::CompilationError(ShaderError {
+                stage: glenum_to_shader_stage(shader_type),
+                log: if log.is_empty() {
+                    "glShaderBinary rejected the binary".to_string()
+                } else {
+                    log
+                },
+            }))
+        }
+    }
+}
+
+/// Returns the info log of a shader.
+unsafe fn get_shader_info_log(ctxt: &mut CommandContext, id: Handle) -> String {
+    let mut error_log_size: gl::types::GLint = mem::uninitialized();
+
+    match id {
+        Handle::Id(id) => {
+            assert!(ctxt.version >= &Version(Api::Gl, 2, 0) ||
+                    ctxt.version >= &Version(Api::GlEs, 2, 0));
+            ctxt.gl.GetShaderiv(id, gl::INFO_LOG_LENGTH, &mut error_log_size);
+        },
+        Handle::Handle(id) => {
+            assert!(ctxt.extensions.gl_arb_shader_objects);
+            ctxt.gl.GetObjectParameterivARB(id, gl::OBJECT_INFO_LOG_LENGTH_ARB,
+                                            &mut error_log_size);
+        }
+    }
+
+    let mut error_log: Vec<u8> = Vec::with_capacity(error_log_size as usize);
+
+    match id {
+        Handle::Id(id) => {
+            assert!(ctxt.version >= &Version(Api::Gl, 2, 0) ||
+                    ctxt.version >= &Version(Api::GlEs, 2, 0));
+            ctxt.gl.GetShaderInfoLog(id, error_log_size, &mut error_log_size,
+                                     error_log.as_mut_ptr() as *mut gl::types::GLchar);
+        },
+        Handle::Handle(id) => {
+            assert!(ctxt.extensions.gl_arb_shader_objects);
+            ctxt.gl.GetInfoLogARB(id, error_log_size, &mut error_log_size,
+                                  error_log.as_mut_ptr() as *mut gl::types::GLchar);
+        }
+    }
+
+    error_log.set_len(error_log_size as usize);
+
+    match String::from_utf8(error_log) {
+        Ok(msg) => msg,
+        Err(_) => "Could not convert the log message to UTF-8".to_string(),
+    }
+}
 
-            error_log.set_len(error_log_size as usize);
+/// Number of lines of source code to show before and after each line that contains an error.
+const CONTEXT_LINES: usize = 2;
 
-            match String::from_utf8(error_log) {
-                Ok(msg) => Err(ProgramCreationError::CompilationError(msg)),
-                Err(_) => Err(
-                    ProgramCreationError::CompilationError("Could not convert the log \
-                                                            message to UTF-8".to_string())
-                ),
+/// Appends to the info log the lines of source code that are mentioned in it.
+///
+/// Returns the log unchanged if no line number could be found in it, or if the source contains
+/// `#line` directives since the line numbers of the log wouldn't match the source.
+pub fn annotate_log(log: &str, source: &str) -> String {
+    if source.lines().any(|l| l.trim_left().starts_with("#line")) {
+        return log.to_string();
+    }
+
+    let mut lines_numbers: Vec<usize> = Vec::new();
+    for line in log.lines() {
+        if let Some(n) = parse_log_line_number(line) {
+            if !lines_numbers.contains(&n) {
+                lines_numbers.push(n);
             }
         }
     }
+
+    if lines_numbers.is_empty() {
+        return log.to_string();
+    }
+
+    let source_lines = source.lines().collect::<Vec<_>>();
+    let mut result = log.trim_right().to_string();
+
+    for &number in lines_numbers.iter() {
+        // line numbers start at 1
+        if number == 0 || number > source_lines.len() {
+            continue;
+        }
+
+        let first = if number > CONTEXT_LINES { number - CONTEXT_LINES } else { 1 };
+        let last = if number + CONTEXT_LINES < source_lines.len() {
+            number + CONTEXT_LINES
+        } else {
+            source_lines.len()
+        };
+
+        result.push_str(&format!("\n\nSource around line {}:", number));
+
+        for current in (first .. last + 1) {
+            let marker = if current == number { ">" } else { " " };
+            result.push_str(&format!("\n{} {:>4} | {}", marker, current,
+                                     source_lines[current - 1]));
+        }
+    }
+
+    result
+}
+
+/// Extracts the line number from a line of an info log.
+///
+/// Handles the `ERROR: 0:12: ...`, `0:12(5): error: ...` and `0(12) : error ...` formats.
+fn parse_log_line_number(line: &str) -> Option<usize> {
+    let mut line = line.trim_left();
+    for prefix in ["ERROR: ", "WARNING: "].iter() {
+        if line.starts_with(*prefix) {
+            line = &line[prefix.len() ..];
+        }
+    }
+
+    // the first number is the index of the source string
+    let string_end = match line.find(|c: char| !c.is_digit(10)) {
+        Some(0) | None => return None,
+        Some(e) => e
+    };
+
+    let rest = &line[string_end ..];
+    let closing = if rest.starts_with(":") {
+        ':'
+    } else if rest.starts_with("(") {
+        ')'
+    } else {
+        return None;
+    };
+
+    let rest = &rest[1 ..];
+    let number_end = match rest.find(|c: char| !c.is_digit(10)) {
+        Some(0) | None => return None,
+        Some(e) => e
+    };
+
+    match rest[number_end ..].chars().next() {
+        Some(c) if c == closing || (closing == ':' && c == '(') => (),
+        _ => return None
+    };
+
+    rest[.. number_end].parse().ok()
 }
 
 fn check_shader_type_compatibility(ctxt: &mut CommandContext, shader_type: gl::types::GLenum)
@@ -208,6 +510,11 @@ fn check_shader_type_compatibility(ctxt: &mut CommandContext, shader_type: gl::t
                 return Err(ProgramCreationError::ShaderTypeNotSupported);
             }
         },
+        gl::MESH_SHADER_NV | gl::TASK_SHADER_NV => {
+            if !ctxt.extensions.gl_nv_mesh_shader {
+                return Err(ProgramCreationError::ShaderTypeNotSupported);
+            }
+        },
         _ => unreachable!()
     };
 
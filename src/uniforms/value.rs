@@ -195,6 +195,25 @@ impl<'a> Clone for UniformValue<'a> {
 }
 
 impl<'a> UniformValue<'a> {
+    /// Returns the type of uniform that this value corresponds to.
+    ///
+    /// Returns `None` for textures, buffers and blocks, which can be used with multiple types
+    /// of uniforms.
+    pub fn get_type(&self) -> Option<UniformType> {
+        match *self {
+            UniformValue::SignedInt(_) => Some(UniformType::Int),
+            UniformValue::UnsignedInt(_) => Some(UniformType::UnsignedInt),
+            UniformValue::Float(_) => Some(UniformType::Float),
+            UniformValue::Mat2(_) => Some(UniformType::FloatMat2),
+            UniformValue::Mat3(_) => Some(UniformType::FloatMat3),
+            UniformValue::Mat4(_) => Some(UniformType::FloatMat4),
+            UniformValue::Vec2(_) => Some(UniformType::FloatVec2),
+            UniformValue::Vec3(_) => Some(UniformType::FloatVec3),
+            UniformValue::Vec4(_) => Some(UniformType::FloatVec4),
+            _ => None,
+        }
+    }
+
     /// Returns true if this value can be used with a uniform of the given type.
     pub fn is_usable_with(&self, ty: &UniformType) -> bool {
         match (self, *ty) {
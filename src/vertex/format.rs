@@ -143,6 +143,29 @@ impl AttributeType {
             AttributeType::F64x4x4 => 16,
         }
     }
+
+    /// Returns the size in bytes of a value of this type.
+    pub fn get_size_bytes(&self) -> usize {
+        let component_size = match *self {
+            AttributeType::I8 | AttributeType::I8I8 | AttributeType::I8I8I8 |
+            AttributeType::I8I8I8I8 | AttributeType::U8 | AttributeType::U8U8 |
+            AttributeType::U8U8U8 | AttributeType::U8U8U8U8 => 1,
+
+            AttributeType::I16 | AttributeType::I16I16 | AttributeType::I16I16I16 |
+            AttributeType::I16I16I16I16 | AttributeType::U16 | AttributeType::U16U16 |
+            AttributeType::U16U16U16 | AttributeType::U16U16U16U16 => 2,
+
+            AttributeType::F64 | AttributeType::F64F64 | AttributeType::F64F64F64 |
+            AttributeType::F64F64F64F64 | AttributeType::F64x2x2 | AttributeType::F64x2x3 |
+            AttributeType::F64x2x4 | AttributeType::F64x3x2 | AttributeType::F64x3x3 |
+            AttributeType::F64x3x4 | AttributeType::F64x4x2 | AttributeType::F64x4x3 |
+            AttributeType::F64x4x4 => 8,
+
+            _ => 4,
+        };
+
+        component_size * self.get_num_components()
+    }
 }
 
 /// Describes the layout of each vertex in a vertex buffer.
@@ -205,3 +205,36 @@ attribute_test!(attribute_vec3_i32, [i32; 3], "vec3", [0, 0, 0], "vec4(field1, 1
 attribute_test!(attribute_vec3_tuple_i32, (i32, i32, i32), "vec3", (0, 0, 0), "vec4(field1, 1.0)");
 attribute_test!(attribute_vec4_i32, [i32; 4], "vec4", [0, 0, 0, 0], "field1");
 attribute_test!(attribute_vec4_tuple_i32, (i32, i32, i32, i32), "vec4", (0, 0, 0, 0), "field1");
+
+#[test]
+fn long_attribute_name() {
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        // vertex shader
+        "
+            #version 110
+
+            attribute vec2 this_is_a_very_long_attribute_name_that_is_more_than_sixty_four_characters;
+
+            void main() {
+                gl_Position = vec4(this_is_a_very_long_attribute_name_that_is_more_than_sixty_four_characters,
+                                   0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+            void main() {
+                gl_FragColor = vec4(0.0, 0.0, 0.0, 1.0);
+            }
+        ",
+
+        // geometry shader
+        None)
+        .unwrap();
+
+    let name = "this_is_a_very_long_attribute_name_that_is_more_than_sixty_four_characters";
+    assert!(program.get_attribute(name).is_some());
+
+    display.assert_no_error();
+}
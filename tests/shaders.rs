@@ -80,6 +80,40 @@ fn program_compilation_error() {
     display.assert_no_error();
 }
 
+#[test]
+fn program_compilation_error_cause() {
+    use std::error::Error;
+
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            void main() {
+                gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        "invalid glsl code",
+        None);
+
+    let err = match program {
+        Err(e) => e,
+        Ok(_) => panic!()
+    };
+
+    match err {
+        glium::CompilationError(ref e) => {
+            assert_eq!(e.stage, Some(glium::program::ShaderStage::Fragment))
+        },
+        _ => panic!()
+    };
+
+    assert!(err.cause().is_some());
+
+    display.assert_no_error();
+}
+
 // This test is disabled because some OpenGL drivers don't catch
 // the linking error (even though they are supposed to)
 #[test]
@@ -122,6 +156,247 @@ fn program_linking_error() {
     display.assert_no_error();
 }
 
+#[test]
+fn program_from_shaders() {
+    let display = support::build_display();
+
+    let vertex_shader = glium::program::Shader::new(&display,
+        glium::program::ShaderStage::Vertex,
+        "
+            #version 110
+
+            void main() {
+                gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+            }
+        ").unwrap();
+
+    let red = glium::program::Shader::new(&display,
+        glium::program::ShaderStage::Fragment,
+        "
+            #version 110
+
+            void main() {
+                gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+            }
+        ").unwrap();
+
+    let green = glium::program::Shader::new(&display,
+        glium::program::ShaderStage::Fragment,
+        "
+            #version 110
+
+            void main() {
+                gl_FragColor = vec4(0.0, 1.0, 0.0, 1.0);
+            }
+        ").unwrap();
+
+    let red = glium::Program::from_shaders(&display, &[&vertex_shader, &red]).unwrap();
+    let green = glium::Program::from_shaders(&display, &[&vertex_shader, &green]).unwrap();
+
+    assert!(red.link_status());
+    assert!(green.link_status());
+
+    display.assert_no_error();
+}
+
+#[test]
+fn program_from_source_verbose() {
+    let display = support::build_display();
+
+    let (program, warnings) = glium::Program::from_source_verbose(&display,
+        "
+            #version 110
+
+            void main() {
+                gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            void main() {
+                gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        None).unwrap();
+
+    assert!(warnings.iter().all(|w| !w.trim().is_empty()));
+    drop(program);
+
+    display.assert_no_error();
+}
+
+#[test]
+fn program_from_combined_source() {
+    let display = support::build_display();
+
+    let program = glium::Program::from_combined_source(&display, "
+        #version 110
+
+        uniform vec4 color;
+
+        #pragma stage vertex
+        void main() {
+            gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+        }
+
+        #pragma stage fragment
+        void main() {
+            gl_FragColor = color;
+        }
+    ").unwrap();
+
+    assert!(program.get_uniform("color").is_some());
+
+    display.assert_no_error();
+}
+
+#[test]
+fn program_from_combined_source_unknown_stage() {
+    let display = support::build_display();
+
+    let program = glium::Program::from_combined_source(&display, "
+        #version 110
+
+        #pragma stage vertex
+        void main() {
+            gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+        }
+
+        #pragma stage pixel
+        void main() {
+            gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+        }
+    ");
+
+    match program {
+        Err(glium::CompilationError(msg)) => assert!(msg.contains("pixel")),
+        _ => panic!()
+    };
+
+    display.assert_no_error();
+}
+
+#[test]
+fn program_batch() {
+    let display = support::build_display();
+
+    let programs = glium::program::with_program_batch(&display, |batch| {
+        (0 .. 4).map(|_| {
+            batch.from_source("
+                #version 110
+
+                void main() {
+                    gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+                }
+            ",
+            "
+                #version 110
+
+                void main() {
+                    gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            ",
+            None).unwrap()
+        }).collect::<Vec<_>>()
+    });
+
+    assert_eq!(programs.len(), 4);
+
+    display.assert_no_error();
+}
+
+#[test]
+fn precompile_shaders() {
+    let display = support::build_display();
+
+    let result = glium::Program::precompile_shaders(&display,
+        glium::program::SourceCode {
+            vertex_shader: "
+                #version 110
+
+                void main() {
+                    gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+                }
+            ",
+            fragment_shader: "
+                #version 110
+
+                void main() {
+                    gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            ",
+            geometry_shader: None,
+            tessellation_control_shader: None,
+            tessellation_evaluation_shader: None,
+        });
+
+    assert!(result.is_ok());
+
+    display.assert_no_error();
+}
+
+#[test]
+fn precompile_shaders_error() {
+    let display = support::build_display();
+
+    let result = glium::Program::precompile_shaders(&display,
+        glium::program::SourceCode {
+            vertex_shader: "invalid glsl code",
+            fragment_shader: "
+                #version 110
+
+                void main() {
+                    gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            ",
+            geometry_shader: None,
+            tessellation_control_shader: None,
+            tessellation_evaluation_shader: None,
+        });
+
+    match result {
+        Err(glium::CompilationError(_)) => (),
+        _ => panic!()
+    };
+
+    display.assert_no_error();
+}
+
+#[test]
+fn program_without_reflection() {
+    let display = support::build_display();
+
+    let program = glium::Program::new_without_reflection(&display,
+        glium::program::SourceCode {
+            vertex_shader: "
+                #version 110
+
+                uniform vec2 offset;
+                attribute vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position + offset, 0.0, 1.0);
+                }
+            ",
+            fragment_shader: "
+                #version 110
+
+                void main() {
+                    gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            ",
+            geometry_shader: None,
+            tessellation_control_shader: None,
+            tessellation_evaluation_shader: None,
+        }).unwrap();
+
+    assert!(program.get_uniform("offset").is_none());
+    assert!(program.get_attribute("position").is_none());
+
+    display.assert_no_error();
+}
+
 #[test]
 fn get_frag_data_location() {    
     let display = support::build_display();
@@ -157,6 +432,36 @@ fn get_frag_data_location() {
     display.assert_no_error();
 }
 
+#[test]
+fn uniform_default_binding() {
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            void main() {
+                gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            uniform sampler2D texture;
+            uniform float factor;
+
+            void main() {
+                gl_FragColor = texture2D(texture, vec2(0.5, 0.5)) * factor;
+            }
+        ",
+        None).unwrap();
+
+    assert_eq!(program.get_uniform("texture").unwrap().default_binding, Some(0));
+    assert_eq!(program.get_uniform("factor").unwrap().default_binding, None);
+
+    display.assert_no_error();
+}
+
 #[test]
 fn get_uniform_blocks() {    
     let display = support::build_display();
@@ -191,12 +496,11 @@ fn get_uniform_blocks() {
         Err(_) => return
     };
 
-    let blocks = program.get_uniform_blocks();
-
-    assert_eq!(blocks.len(), 1);
-    assert!(blocks.get("MyBlock").is_some());
+    assert_eq!(program.uniform_blocks().len(), 1);
+    assert_eq!(program.uniform_blocks().next().map(|(n, _)| n), Some("MyBlock"));
+    assert!(program.get_uniform_block("MyBlock").is_some());
 
-    let my_block = blocks.get("MyBlock").unwrap();
+    let my_block = program.get_uniform_block("MyBlock").unwrap();
     assert!(my_block.size >= 3 * 4 + 4 * 12);
     assert_eq!(my_block.members.len(), 2);
 
@@ -217,133 +521,314 @@ fn get_uniform_blocks() {
 }
 
 #[test]
-#[ignore]       // TODO: doesn't work with some versions of MESA
-fn get_program_binary() {
+fn long_uniform_block_name() {
     let display = support::build_display();
 
     let program = glium::Program::from_source(&display,
         "
-            #version 110
-
-            uniform mat4 matrix;
-
-            attribute vec2 position;
-            attribute vec3 color;
+            #version 330
 
-            varying vec3 vColor;
+            uniform ThisIsAVeryLongUniformBlockNameThatIsLongerThanSixtyFourCharactersLong {
+                vec4 position;
+            };
 
             void main() {
-                gl_Position = vec4(position, 0.0, 1.0) * matrix;
-                vColor = color;
+                gl_Position = position;
             }
         ",
         "
-            #version 110
-            varying vec3 vColor;
+            #version 130
+
+            out vec4 color;
 
             void main() {
-                gl_FragColor = vec4(vColor, 1.0);
+                color = vec4(1.0, 1.0, 1.0, 1.0);
             }
         ",
-        None).unwrap();
+        None);
 
-    let binary = match program.get_binary_if_supported() {
-        None => return,
-        Some(bin) => bin
+    // ignoring test in case of compilation error (version may not be supported)
+    let program = match program {
+        Ok(p) => p,
+        Err(_) => return
     };
 
-    assert!(binary.content.len() >= 1);
+    let name = "ThisIsAVeryLongUniformBlockNameThatIsLongerThanSixtyFourCharactersLong";
+    assert!(program.get_uniform_block(name).is_some());
+    assert_eq!(program.uniform_blocks().next().map(|(n, _)| n), Some(name));
 
     display.assert_no_error();
 }
 
 #[test]
-#[ignore]       // TODO: doesn't work with some versions of MESA
-fn program_binary_reload() {
+fn get_uniform_block_size() {
     let display = support::build_display();
 
     let program = glium::Program::from_source(&display,
         "
-            #version 110
-
-            uniform mat4 matrix;
-
-            attribute vec2 position;
-            attribute vec3 color;
+            #version 330
 
-            varying vec3 vColor;
+            layout(std140) uniform MyBlock {
+                vec4 position;
+                vec2 offset;
+                float values[2];
+            };
 
             void main() {
-                gl_Position = vec4(position, 0.0, 1.0) * matrix;
-                vColor = color;
+                gl_Position = position + vec4(offset, values[0], values[1]);
             }
         ",
         "
-            #version 110
-            varying vec3 vColor;
+            #version 130
+
+            out vec4 color;
 
             void main() {
-                gl_FragColor = vec4(vColor, 1.0);
+                color = vec4(1.0, 1.0, 1.0, 1.0);
             }
         ",
-        None).unwrap();
+        None);
 
-    let binary = match program.get_binary_if_supported() {
-        None => return,
-        Some(bin) => bin
+    // ignoring test in case of compilation error (version may not be supported)
+    let program = match program {
+        Ok(p) => p,
+        Err(_) => return
     };
 
-    let _program2 = glium::Program::new(&display, binary).unwrap();
+    // with std140, each element of the array is aligned to 16 bytes
+    assert_eq!(program.get_uniform_block_size("MyBlock"), Some(16 + 16 + 2 * 16));
+    assert_eq!(program.get_uniform_block_size("NonExistingBlock"), None);
 
     display.assert_no_error();
 }
 
 #[test]
-#[ignore]       // TODO: doesn't work with some versions of MESA
-fn program_binary_working() {
+fn uniform_block_invalid_names() {
     let display = support::build_display();
-    let (vb, ib) = support::build_rectangle_vb_ib(&display);
 
-    let program_src = glium::Program::from_source(&display,
+    let program = glium::Program::from_source(&display,
         "
-            #version 110
+            #version 330
 
-            attribute vec2 position;
+            uniform MyBlock {
+                vec4 position;
+            };
 
             void main() {
-                gl_Position = vec4(position, 0.0, 1.0);
+                gl_Position = position;
             }
         ",
         "
-            #version 110
+            #version 130
+
+            out vec4 color;
 
             void main() {
-                gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+                color = vec4(1.0, 1.0, 1.0, 1.0);
             }
         ",
-        None).unwrap();
+        None);
 
-    let binary = match program_src.get_binary_if_supported() {
-        None => return,
-        Some(bin) => bin
+    // ignoring test in case of compilation error (version may not be supported)
+    let program = match program {
+        Ok(p) => p,
+        Err(_) => return
     };
 
-    let program = glium::Program::new(&display, binary).unwrap();
+    let indices = program.get_uniform_indices(&["position", "bogus"]);
+    assert_eq!(indices.len(), 2);
+    assert_eq!(indices[1], None);
 
-    let output = support::build_renderable_texture(&display);
-    output.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
-    output.as_surface().draw(&vb, &ib, &program, &uniform!{}, &Default::default()).unwrap();
+    assert!(program.set_uniform_block_binding("bogus", 0).is_err());
 
-    let data: Vec<Vec<(f32, f32, f32, f32)>> = output.read();
-    for row in data.iter() {
-        for pixel in row.iter() {
-            assert_eq!(pixel, &(1.0, 0.0, 0.0, 1.0));
+    display.assert_no_error();
+}
+
+#[test]
+#[ignore]       // TODO: doesn't work with some versions of MESA
+fn get_program_binary() {
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            uniform mat4 matrix;
+
+            attribute vec2 position;
+            attribute vec3 color;
+
+            varying vec3 vColor;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0) * matrix;
+                vColor = color;
+            }
+        ",
+        "
+            #version 110
+            varying vec3 vColor;
+
+            void main() {
+                gl_FragColor = vec4(vColor, 1.0);
+            }
+        ",
+        None).unwrap();
+
+    let binary = match program.get_binary_if_supported() {
+        None => return,
+        Some(bin) => bin
+    };
+
+    assert!(binary.content.len() >= 1);
+    assert!(display.get_program_binary_formats().contains(&binary.format));
+    assert!(binary.is_format_supported(&display));
+
+    display.assert_no_error();
+}
+
+#[test]
+fn shader_binaries_unsupported_format() {
+    let display = support::build_display();
+
+    // no implementation uses 0 as a format
+    assert!(!display.get_shader_binary_formats().contains(&0));
+
+    let data = [0u8; 16];
+    let program = glium::Program::new(&display,
+        glium::program::ProgramCreationInput::ShaderBinaries {
+            stages: vec![
+                (glium::program::ShaderStage::Vertex, 0, &data[..]),
+                (glium::program::ShaderStage::Fragment, 0, &data[..]),
+            ],
+            retain_link_log: false,
+            reflect_builtins: false,
+        });
+
+    match program {
+        Err(glium::program::ProgramCreationError::CompilationNotSupported) => (),
+        _ => panic!()
+    };
+
+    display.assert_no_error();
+}
+
+#[test]
+#[ignore]       // TODO: doesn't work with some versions of MESA
+fn program_binary_reload() {
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            uniform mat4 matrix;
+
+            attribute vec2 position;
+            attribute vec3 color;
+
+            varying vec3 vColor;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0) * matrix;
+                vColor = color;
+            }
+        ",
+        "
+            #version 110
+            varying vec3 vColor;
+
+            void main() {
+                gl_FragColor = vec4(vColor, 1.0);
+            }
+        ",
+        None).unwrap();
+
+    let binary = match program.get_binary_if_supported() {
+        None => return,
+        Some(bin) => bin
+    };
+
+    let _program2 = glium::Program::new(&display, binary).unwrap();
+
+    display.assert_no_error();
+}
+
+#[test]
+#[ignore]       // TODO: doesn't work with some versions of MESA
+fn program_binary_working() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let program_src = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            attribute vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            void main() {
+                gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        None).unwrap();
+
+    let binary = match program_src.get_binary_if_supported() {
+        None => return,
+        Some(bin) => bin
+    };
+
+    let program = glium::Program::new(&display, binary).unwrap();
+
+    let output = support::build_renderable_texture(&display);
+    output.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    output.as_surface().draw(&vb, &ib, &program, &uniform!{}, &Default::default()).unwrap();
+
+    let data: Vec<Vec<(f32, f32, f32, f32)>> = output.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(1.0, 0.0, 0.0, 1.0));
         }
     }
 
     display.assert_no_error();
 }
 
+#[test]
+fn get_disassembly() {
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            void main() {
+                gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            void main() {
+                gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        None).unwrap();
+
+    // the result depends on the driver, we just check that nothing goes wrong
+    if let Some(asm) = program.get_disassembly() {
+        assert!(asm.len() >= 1);
+    }
+
+    display.assert_no_error();
+}
+
 #[test]
 fn get_transform_feedback_varyings() {    
     let display = support::build_display();
@@ -353,15 +838,17 @@ fn get_transform_feedback_varyings() {
         tessellation_evaluation_shader: None,
         geometry_shader: None,
 
+        // GLSL 1.10 doesn't have integer varyings, and the type of `color` must match the
+        // `U32` checked below
         vertex_shader: "
-            #version 110
+            #version 130
 
-            varying vec2 normal;
-            varying int color;
+            out vec2 normal;
+            flat out uint color;
 
             void main() {
                 normal = vec2(0.0, 0.0);
-                color = 5;
+                color = 5u;
 
                 gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
             }
@@ -380,6 +867,14 @@ fn get_transform_feedback_varyings() {
             vec!["normal".to_string(), "color".to_string()],
             glium::program::TransformFeedbackMode::Separate
         )),
+
+        indexed_frag_data_locations: None,
+        source_transform: None,
+        retain_link_log: false,
+        retain_source: false,
+        strict: false,
+        missing_version: glium::program::MissingVersion::Ignore,
+        reflect_builtins: false,
     };
 
     let program = match glium::Program::new(&display, source) {
@@ -392,6 +887,8 @@ fn get_transform_feedback_varyings() {
                 glium::program::TransformFeedbackVarying {
                     name: "normal".to_string(),
                     size: 2 * 4,
+                    offset: 0,
+                    num_components: 2,
                     ty: glium::vertex::AttributeType::F32F32,
                 });
 
@@ -399,6 +896,8 @@ fn get_transform_feedback_varyings() {
                 glium::program::TransformFeedbackVarying {
                     name: "color".to_string(),
                     size: 4,
+                    offset: 0,
+                    num_components: 1,
                     ty: glium::vertex::AttributeType::U32,
                 });
 
@@ -406,6 +905,2096 @@ fn get_transform_feedback_varyings() {
 
     assert_eq!(program.get_transform_feedback_mode(),
                Some(glium::program::TransformFeedbackMode::Separate));
+    assert_eq!(program.transform_feedback_buffer_count_required(), 2);
     
     display.assert_no_error();
 }
+
+#[test]
+fn transform_feedback_varying_misspelled() {
+    let display = support::build_display();
+
+    let source = glium::program::ProgramCreationInput::SourceCode {
+        tessellation_control_shader: None,
+        tessellation_evaluation_shader: None,
+        geometry_shader: None,
+
+        vertex_shader: "
+            #version 110
+
+            varying vec2 normal;
+
+            void main() {
+                normal = vec2(0.0, 0.0);
+                gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        fragment_shader: "
+            #version 110
+
+            void main() {
+                gl_FragColor = vec4(1.0, 1.0, 1.0, 1.0);
+            }
+        ",
+
+        transform_feedback_varyings: Some((
+            vec!["nromal".to_string()],
+            glium::program::TransformFeedbackMode::Separate
+        )),
+
+        indexed_frag_data_locations: None,
+        source_transform: None,
+        retain_link_log: false,
+        retain_source: false,
+        strict: false,
+        missing_version: glium::program::MissingVersion::Ignore,
+        reflect_builtins: false,
+    };
+
+    match glium::Program::new(&display, source) {
+        Err(glium::program::ProgramCreationError::TransformFeedbackNotSupported) => return,
+        Err(glium::program::ProgramCreationError::LinkingError(msg)) => {
+            assert!(msg.contains("`nromal`"));
+        },
+        _ => panic!()
+    };
+
+    display.assert_no_error();
+}
+
+#[test]
+fn get_output() {
+    let display = support::build_display();
+
+    let program = match glium::Program::from_source(&display,
+        "
+            #version 330
+
+            void main() {
+                gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 330
+
+            layout(location = 1) out vec4 color;
+
+            void main() {
+                color = vec4(1.0, 1.0, 1.0, 1.0);
+            }
+        ",
+        None)
+    {
+        Ok(p) => p,
+        Err(_) => return
+    };
+
+    // outputs can't be queried on old backends
+    let output = match program.get_output("color") {
+        Some(o) => o,
+        None => return
+    };
+
+    assert_eq!(output.location, 1);
+    assert_eq!(output.ty, glium::vertex::AttributeType::F32F32F32F32);
+    assert!(output.component.unwrap_or(0) == 0);
+
+    display.assert_no_error();
+}
+
+#[test]
+fn fragment_outputs() {
+    let display = support::build_display();
+
+    let program = match glium::Program::from_source(&display,
+        "
+            #version 330
+
+            void main() {
+                gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 330
+
+            layout(location = 0) out vec4 color;
+            layout(location = 2) out vec4 normal;
+
+            void main() {
+                color = vec4(1.0, 1.0, 1.0, 1.0);
+                normal = vec4(0.0, 0.0, 1.0, 0.0);
+            }
+        ",
+        None)
+    {
+        Ok(p) => p,
+        Err(_) => return
+    };
+
+    let mut outputs = program.fragment_outputs().collect::<Vec<_>>();
+
+    // outputs can't be enumerated on old backends
+    if outputs.is_empty() {
+        return;
+    }
+
+    outputs.sort_by(|a, b| a.1.cmp(&b.1));
+    assert_eq!(outputs, vec![("color", 0), ("normal", 2)]);
+    assert_eq!(program.fragment_output_count(), Some(2));
+
+    display.assert_no_error();
+}
+
+#[test]
+fn recompile_with_defines() {
+    let display = support::build_display();
+
+    let program = glium::Program::new(&display,
+        glium::program::ProgramCreationInput::SourceCode {
+            vertex_shader: RECOMPILE_VERTEX_SHADER,
+            tessellation_control_shader: None,
+            tessellation_evaluation_shader: None,
+            geometry_shader: None,
+            fragment_shader: RECOMPILE_FRAGMENT_SHADER,
+            transform_feedback_varyings: None,
+            indexed_frag_data_locations: None,
+            source_transform: None,
+            retain_link_log: false,
+            retain_source: true,
+            strict: false,
+            missing_version: glium::program::MissingVersion::Ignore,
+            reflect_builtins: false,
+        }).unwrap();
+
+    assert!(program.get_uniform("color").is_none());
+
+    let variant = program.recompile_with_defines(&display, &[("WITH_COLOR", "1")]).unwrap();
+    assert!(variant.get_uniform("color").is_some());
+
+    assert!(variant.source_hash().is_some());
+    assert!(variant.source_hash() != program.source_hash());
+
+    // the defines don't accumulate
+    let variant = variant.recompile_with_defines(&display, &[]).unwrap();
+    assert!(variant.get_uniform("color").is_none());
+
+    display.assert_no_error();
+}
+
+#[test]
+fn recompile_with_defines_source_not_retained() {
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display, RECOMPILE_VERTEX_SHADER,
+                                              RECOMPILE_FRAGMENT_SHADER, None).unwrap();
+
+    match program.recompile_with_defines(&display, &[("WITH_COLOR", "1")]) {
+        Err(glium::ProgramCreationError::SourceNotAvailable) => (),
+        _ => panic!()
+    };
+
+    display.assert_no_error();
+}
+
+#[test]
+fn recompile_with_defines_drops_transform() {
+    let display = support::build_display();
+
+    let transform = |_: glium::program::ShaderStage, source: &str| -> Result<String, String> {
+        Ok(source.replace("#version 110", "#version 110\n#define WITH_COLOR 1"))
+    };
+
+    let program = glium::Program::new(&display,
+        glium::program::ProgramCreationInput::SourceCode {
+            vertex_shader: RECOMPILE_VERTEX_SHADER,
+            tessellation_control_shader: None,
+            tessellation_evaluation_shader: None,
+            geometry_shader: None,
+            fragment_shader: RECOMPILE_FRAGMENT_SHADER,
+            transform_feedback_varyings: None,
+            indexed_frag_data_locations: None,
+            source_transform: Some(&transform),
+            retain_link_log: false,
+            retain_source: true,
+            strict: false,
+            missing_version: glium::program::MissingVersion::Ignore,
+            reflect_builtins: false,
+        }).unwrap();
+
+    assert!(program.get_uniform("color").is_some());
+
+    // the transform is not applied again
+    let variant = program.recompile_with_defines(&display, &[]).unwrap();
+    assert!(variant.get_uniform("color").is_none());
+
+    display.assert_no_error();
+}
+
+static RECOMPILE_VERTEX_SHADER: &'static str = "
+    #version 110
+
+    void main() {
+        gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+    }
+";
+
+static RECOMPILE_FRAGMENT_SHADER: &'static str = "
+    #version 110
+
+    #ifdef WITH_COLOR
+    uniform vec4 color;
+    #endif
+
+    void main() {
+    #ifdef WITH_COLOR
+        gl_FragColor = color;
+    #else
+        gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+    #endif
+    }
+";
+
+#[test]
+fn creation_context_info() {
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            void main() {
+                gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            void main() {
+                gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        None).unwrap();
+
+    let info = program.creation_context_info();
+    assert_eq!(info.version, display.get_opengl_version());
+
+    display.assert_no_error();
+}
+
+#[test]
+fn refresh_reflection() {
+    let display = support::build_display();
+
+    let mut program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            uniform vec2 offset;
+
+            void main() {
+                gl_Position = vec4(offset, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            void main() {
+                gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        None).unwrap();
+
+    assert!(program.get_uniform("offset").is_some());
+    program.refresh_reflection().unwrap();
+    assert!(program.get_uniform("offset").is_some());
+
+    assert_eq!(program.uniform_count(), 1);
+    assert_eq!(program.attribute_count(), 0);
+    assert_eq!(program.uniform_block_count(), 0);
+
+    display.assert_no_error();
+}
+
+#[test]
+fn program_gl_id() {
+    use glium::GlObject;
+
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            void main() {
+                gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            void main() {
+                gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        None).unwrap();
+
+    match program.get_id() {
+        glium::Handle::Id(id) => {
+            assert_eq!(program.gl_id(), Some(id));
+            assert_eq!(program.handle_debug_string(), format!("id={}", id));
+        },
+        glium::Handle::Handle(_) => {
+            assert_eq!(program.gl_id(), None);
+            assert!(program.handle_debug_string().starts_with("arb_handle=0x"));
+        },
+    };
+
+    display.assert_no_error();
+}
+
+#[test]
+fn image_uniforms() {
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 420
+
+            uniform vec2 offset;
+
+            void main() {
+                gl_Position = vec4(offset, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 420
+
+            layout(binding = 3, rgba8) uniform writeonly image2D img;
+            out vec4 color;
+
+            void main() {
+                imageStore(img, ivec2(0, 0), vec4(1.0, 0.0, 0.0, 1.0));
+                color = vec4(1.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        None);
+
+    // ignoring test in case of compilation error (version may not be supported)
+    let program = match program {
+        Ok(p) => p,
+        Err(_) => return
+    };
+
+    let images = program.image_uniforms().collect::<Vec<_>>();
+    assert_eq!(images.len(), 1);
+    assert_eq!(images[0].0, "img");
+    assert_eq!(images[0].1.ty, glium::uniforms::UniformType::Image2d);
+    assert_eq!(images[0].1.binding, 3);
+
+    display.assert_no_error();
+}
+
+#[test]
+fn request_binary_retention() {
+    let display = support::build_display();
+
+    let mut program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            uniform vec2 offset;
+
+            void main() {
+                gl_Position = vec4(offset, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            void main() {
+                gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        None).unwrap();
+
+    match program.request_binary_retention() {
+        Ok(()) => (),
+        Err(glium::program::ProgramCreationError::BinaryNotSupported) => return,
+        Err(e) => panic!("{:?}", e)
+    };
+
+    assert!(program.get_uniform("offset").is_some());
+    assert!(program.get_uniform_default("offset").is_some());
+
+    display.assert_no_error();
+}
+
+#[test]
+fn source_hash() {
+    let display = support::build_display();
+
+    let vertex_shader = "
+        #version 110
+
+        void main() {
+            gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+        }
+    ";
+
+    let fragment_shader = "
+        #version 110
+
+        void main() {
+            gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+        }
+    ";
+
+    let program1 = glium::Program::from_source(&display, vertex_shader, fragment_shader,
+                                               None).unwrap();
+    let program2 = glium::Program::from_source(&display, vertex_shader, fragment_shader,
+                                               None).unwrap();
+
+    assert!(program1.source_hash().is_some());
+    assert_eq!(program1.source_hash(), program2.source_hash());
+
+    // the hash is computed from the source code after the transform
+    let transform = |_: glium::program::ShaderStage, source: &str| -> Result<String, String> {
+        Ok(format!("{}\n// transformed\n", source))
+    };
+
+    let program3 = glium::Program::new(&display,
+        glium::program::ProgramCreationInput::SourceCode {
+            vertex_shader: vertex_shader,
+            tessellation_control_shader: None,
+            tessellation_evaluation_shader: None,
+            geometry_shader: None,
+            fragment_shader: fragment_shader,
+            transform_feedback_varyings: None,
+            indexed_frag_data_locations: None,
+            source_transform: Some(&transform),
+            retain_link_log: false,
+            retain_source: false,
+            strict: false,
+            missing_version: glium::program::MissingVersion::Ignore,
+            reflect_builtins: false,
+        }).unwrap();
+
+    assert!(program3.source_hash().is_some());
+    assert!(program3.source_hash() != program1.source_hash());
+
+    display.assert_no_error();
+}
+
+#[test]
+fn mesh_shader() {
+    let display = support::build_display();
+
+    let mesh = glium::program::Shader::new(&display,
+        glium::program::ShaderStage::Mesh,
+        "
+            #version 450
+            #extension GL_NV_mesh_shader : require
+
+            layout(local_size_x = 1) in;
+            layout(triangles, max_vertices = 3, max_primitives = 1) out;
+
+            void main() {
+                gl_MeshVerticesNV[0].gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+                gl_MeshVerticesNV[1].gl_Position = vec4(1.0, 0.0, 0.0, 1.0);
+                gl_MeshVerticesNV[2].gl_Position = vec4(0.0, 1.0, 0.0, 1.0);
+                gl_PrimitiveIndicesNV[0] = 0;
+                gl_PrimitiveIndicesNV[1] = 1;
+                gl_PrimitiveIndicesNV[2] = 2;
+                gl_PrimitiveCountNV = 1;
+            }
+        ");
+
+    let mesh = match mesh {
+        Ok(s) => s,
+        Err(glium::program::ProgramCreationError::ShaderTypeNotSupported) => return,
+        Err(e) => panic!("{:?}", e)
+    };
+
+    assert_eq!(mesh.get_stage(), glium::program::ShaderStage::Mesh);
+
+    display.assert_no_error();
+}
+
+#[test]
+fn indexed_frag_data_locations() {
+    let display = support::build_display();
+
+    let source = glium::program::ProgramCreationInput::SourceCode {
+        tessellation_control_shader: None,
+        tessellation_evaluation_shader: None,
+        geometry_shader: None,
+
+        vertex_shader: "
+            #version 330
+
+            void main() {
+                gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        fragment_shader: "
+            #version 330
+
+            out vec4 color;
+            out vec4 factor;
+
+            void main() {
+                color = vec4(1.0, 1.0, 1.0, 1.0);
+                factor = vec4(0.5, 0.5, 0.5, 0.5);
+            }
+        ",
+
+        transform_feedback_varyings: None,
+        indexed_frag_data_locations: Some(&[("color", 0, 0), ("factor", 0, 1)]),
+        source_transform: None,
+        retain_link_log: false,
+        retain_source: false,
+        strict: false,
+        missing_version: glium::program::MissingVersion::Ignore,
+        reflect_builtins: false,
+    };
+
+    let program = match glium::Program::new(&display, source) {
+        Ok(p) => p,
+        Err(glium::program::ProgramCreationError::DualSourceBlendingNotSupported) => return,
+        Err(e) => panic!("{:?}", e)
+    };
+
+    assert_eq!(program.get_frag_data_location("color"), Some(0));
+    assert_eq!(program.get_frag_data_index("color"), Some(0));
+    assert_eq!(program.get_frag_data_location("factor"), Some(0));
+    assert_eq!(program.get_frag_data_index("factor"), Some(1));
+
+    display.assert_no_error();
+}
+
+#[test]
+fn uniforms_matching() {
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            uniform vec2 offset;
+            uniform float material_shininess;
+
+            void main() {
+                gl_Position = vec4(offset, material_shininess, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            uniform vec4 material_color;
+
+            void main() {
+                gl_FragColor = material_color;
+            }
+        ",
+        None).unwrap();
+
+    let mut names = program.uniforms_matching(|name, _| name.starts_with("material_"))
+                           .map(|(name, _)| name).collect::<Vec<_>>();
+    names.sort();
+
+    assert_eq!(names, vec!["material_color", "material_shininess"]);
+
+    display.assert_no_error();
+}
+
+#[test]
+fn tessellation_control_without_evaluation() {
+    let display = support::build_display();
+
+    let program = glium::Program::new(&display,
+        glium::program::SourceCode {
+            vertex_shader: "
+                #version 400
+
+                void main() {
+                    gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+                }
+            ",
+            tessellation_control_shader: Some("
+                #version 400
+
+                layout(vertices = 3) out;
+
+                void main() {
+                    gl_out[gl_InvocationID].gl_Position = gl_in[gl_InvocationID].gl_Position;
+                    gl_TessLevelOuter[0] = 1.0;
+                }
+            "),
+            tessellation_evaluation_shader: None,
+            geometry_shader: None,
+            fragment_shader: "
+                #version 400
+
+                out vec4 color;
+
+                void main() {
+                    color = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            ",
+        });
+
+    match program {
+        Err(glium::LinkingError(e)) => {
+            assert_eq!(e.stage, Some(glium::program::ShaderStage::TessellationControl));
+        },
+        _ => panic!()
+    };
+
+    display.assert_no_error();
+}
+
+#[test]
+fn source_transform() {
+    let display = support::build_display();
+
+    let transform = |stage: glium::program::ShaderStage, source: &str|
+                     -> Result<String, String>
+    {
+        match stage {
+            glium::program::ShaderStage::Fragment => Ok(source.replace("INVALID", "vec4")),
+            _ => Ok(source.to_string())
+        }
+    };
+
+    let source = glium::program::ProgramCreationInput::SourceCode {
+        tessellation_control_shader: None,
+        tessellation_evaluation_shader: None,
+        geometry_shader: None,
+
+        vertex_shader: "
+            #version 110
+
+            void main() {
+                gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        fragment_shader: "
+            #version 110
+
+            void main() {
+                gl_FragColor = INVALID(1.0, 1.0, 1.0, 1.0);
+            }
+        ",
+
+        transform_feedback_varyings: None,
+        indexed_frag_data_locations: None,
+        source_transform: Some(&transform),
+        retain_link_log: false,
+        retain_source: false,
+        strict: false,
+        missing_version: glium::program::MissingVersion::Ignore,
+        reflect_builtins: false,
+    };
+
+    glium::Program::new(&display, source).unwrap();
+
+    display.assert_no_error();
+}
+
+#[test]
+fn source_transform_error() {
+    let display = support::build_display();
+
+    let transform = |_: glium::program::ShaderStage, _: &str| -> Result<String, String> {
+        Err("file not found".to_string())
+    };
+
+    let source = glium::program::ProgramCreationInput::SourceCode {
+        tessellation_control_shader: None,
+        tessellation_evaluation_shader: None,
+        geometry_shader: None,
+
+        vertex_shader: "
+            #version 110
+
+            #include \"common.glsl\"
+
+            void main() {
+                gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        fragment_shader: "
+            #version 110
+
+            void main() {
+                gl_FragColor = vec4(1.0, 1.0, 1.0, 1.0);
+            }
+        ",
+
+        transform_feedback_varyings: None,
+        indexed_frag_data_locations: None,
+        source_transform: Some(&transform),
+        retain_link_log: false,
+        retain_source: false,
+        strict: false,
+        missing_version: glium::program::MissingVersion::Ignore,
+        reflect_builtins: false,
+    };
+
+    match glium::Program::new(&display, source) {
+        Err(glium::program::ProgramCreationError::PreprocessingError(e)) => {
+            assert_eq!(e, "file not found");
+        },
+        _ => panic!()
+    };
+
+    display.assert_no_error();
+}
+
+#[test]
+fn get_uniform_ci() {
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            uniform vec2 Offset;
+
+            void main() {
+                gl_Position = vec4(Offset, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            void main() {
+                gl_FragColor = vec4(1.0, 1.0, 1.0, 1.0);
+            }
+        ",
+        None).unwrap();
+
+    assert!(program.get_uniform("offset").is_none());
+    assert!(program.get_uniform_ci("offset").is_some());
+    assert!(program.get_uniform_ci("OFFSET").is_some());
+    assert!(program.get_uniform_ci("offsets").is_none());
+
+    display.assert_no_error();
+}
+
+#[test]
+fn build_attribute_bindings() {
+    use glium::program::BindingError;
+
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            attribute vec2 position;
+            attribute vec3 color;
+
+            varying vec3 v_color;
+
+            void main() {
+                v_color = color;
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            varying vec3 v_color;
+
+            void main() {
+                gl_FragColor = vec4(v_color, 1.0);
+            }
+        ",
+        None).unwrap();
+
+    let bindings = program.build_attribute_bindings(&[("position", 2), ("color", 3)]).unwrap();
+    assert_eq!(bindings.len(), 2);
+    assert_eq!(bindings[0].name, "position");
+    assert_eq!(bindings[0].location, program.get_attribute("position").unwrap().location);
+    assert_eq!(bindings[1].num_components, 3);
+
+    match program.build_attribute_bindings(&[("position", 2), ("colour", 3)]) {
+        Err(BindingError::UnknownAttribute(ref name)) if name == "colour" => (),
+        _ => panic!()
+    };
+
+    match program.build_attribute_bindings(&[("position", 2)]) {
+        Err(BindingError::MissingAttribute(ref name)) if name == "color" => (),
+        _ => panic!()
+    };
+
+    match program.build_attribute_bindings(&[("position", 3), ("color", 3)]) {
+        Err(BindingError::ComponentCountMismatch { expected: 2, obtained: 3, .. }) => (),
+        _ => panic!()
+    };
+
+    display.assert_no_error();
+}
+
+#[test]
+fn link_log() {
+    let display = support::build_display();
+
+    let source = |retain_link_log| glium::program::ProgramCreationInput::SourceCode {
+        tessellation_control_shader: None,
+        tessellation_evaluation_shader: None,
+        geometry_shader: None,
+
+        vertex_shader: "
+            #version 110
+
+            void main() {
+                gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        fragment_shader: "
+            #version 110
+
+            void main() {
+                gl_FragColor = vec4(1.0, 1.0, 1.0, 1.0);
+            }
+        ",
+
+        transform_feedback_varyings: None,
+        indexed_frag_data_locations: None,
+        source_transform: None,
+        retain_link_log: retain_link_log,
+        retain_source: false,
+        strict: false,
+        missing_version: glium::program::MissingVersion::Ignore,
+        reflect_builtins: false,
+    };
+
+    let program = glium::Program::new(&display, source(false)).unwrap();
+    assert!(program.link_log().is_none());
+
+    let program = glium::Program::new(&display, source(true)).unwrap();
+    assert!(program.link_log().is_some());
+
+    display.assert_no_error();
+}
+
+#[test]
+fn attribute_location() {
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            attribute vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            void main() {
+                gl_FragColor = vec4(1.0, 1.0, 1.0, 1.0);
+            }
+        ",
+        None).unwrap();
+
+    assert_eq!(program.attribute_location("position"),
+               program.get_attribute("position").unwrap().location);
+    assert_eq!(program.attribute_location("normal"), -1);
+
+    display.assert_no_error();
+}
+
+#[test]
+fn strict_inert_geometry_shader() {
+    let display = support::build_display();
+
+    let source = |strict| glium::program::ProgramCreationInput::SourceCode {
+        tessellation_control_shader: None,
+        tessellation_evaluation_shader: None,
+
+        vertex_shader: "
+            #version 150
+
+            void main() {
+                gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        geometry_shader: Some("
+            #version 150
+
+            layout(triangles) in;
+            layout(triangle_strip, max_vertices = 3) out;
+
+            void main() {
+            }
+        "),
+        fragment_shader: "
+            #version 150
+
+            out vec4 color;
+
+            void main() {
+                color = vec4(1.0, 1.0, 1.0, 1.0);
+            }
+        ",
+
+        transform_feedback_varyings: None,
+        indexed_frag_data_locations: None,
+        source_transform: None,
+        retain_link_log: false,
+        strict: strict,
+        missing_version: glium::program::MissingVersion::Ignore,
+        reflect_builtins: false,
+    };
+
+    match glium::Program::new(&display, source(false)) {
+        Ok(_) => (),
+        Err(glium::program::ProgramCreationError::ShaderTypeNotSupported) => return,
+        Err(e) => panic!("{:?}", e)
+    };
+
+    match glium::Program::new(&display, source(true)) {
+        Err(glium::LinkingError(e)) => {
+            assert_eq!(e.stage, Some(glium::program::ShaderStage::Geometry));
+        },
+        _ => panic!()
+    };
+
+    display.assert_no_error();
+}
+
+#[test]
+fn query_resource() {
+    const GL_UNIFORM: u32 = 0x92E1;
+    const GL_LOCATION: u32 = 0x930E;
+
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            uniform vec2 offset;
+
+            void main() {
+                gl_Position = vec4(offset, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            void main() {
+                gl_FragColor = vec4(1.0, 1.0, 1.0, 1.0);
+            }
+        ",
+        None).unwrap();
+
+    let values = match program.query_resource(GL_UNIFORM, "offset", &[GL_LOCATION]) {
+        Some(v) => v,
+        None => return
+    };
+
+    assert_eq!(values, vec![program.get_uniform("offset").unwrap().location]);
+    assert!(program.query_resource(GL_UNIFORM, "unknown", &[GL_LOCATION]).is_none());
+
+    display.assert_no_error();
+}
+
+#[test]
+fn uniform_block_members() {
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 330
+
+            layout(std140) uniform MyBlock {
+                mat4 matrix;
+                float values[2];
+            };
+
+            void main() {
+                gl_Position = matrix * vec4(values[0], values[1], 0.0, 1.0);
+            }
+        ",
+        "
+            #version 130
+
+            out vec4 color;
+
+            void main() {
+                color = vec4(1.0, 1.0, 1.0, 1.0);
+            }
+        ",
+        None);
+
+    // ignoring test in case of compilation error (version may not be supported)
+    let program = match program {
+        Ok(p) => p,
+        Err(_) => return
+    };
+
+    let members = program.uniform_block_members("MyBlock").unwrap().collect::<Vec<_>>();
+    assert_eq!(members.len(), 2);
+
+    let matrix = members.iter().find(|m| m.name == "matrix").unwrap();
+    assert_eq!(matrix.offset, 0);
+    assert_eq!(matrix.matrix_stride, 16);
+
+    let values = members.iter().find(|m| m.name.starts_with("values")).unwrap();
+    assert_eq!(values.offset, 64);
+    assert_eq!(values.array_stride, 16);
+
+    assert!(program.uniform_block_members("NonExistingBlock").is_none());
+
+    assert_eq!(program.num_uniform_block_members("MyBlock"), Some(2));
+    assert_eq!(program.num_uniform_block_members("NonExistingBlock"), None);
+
+    let stages = program.uniform_block_stages("MyBlock").unwrap();
+    assert!(stages.contains(glium::program::ShaderStage::Vertex));
+    assert!(!stages.contains(glium::program::ShaderStage::Fragment));
+    assert!(program.uniform_block_stages("NonExistingBlock").is_none());
+
+    display.assert_no_error();
+}
+
+#[test]
+fn program_binaries_candidates() {
+    let display = support::build_display();
+
+    let program_src = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            attribute vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            void main() {
+                gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        None).unwrap();
+
+    let binary = match program_src.get_binary_if_supported() {
+        None => return,
+        Some(bin) => bin
+    };
+
+    let invalid = glium::program::Binary {
+        format: binary.format,
+        content: vec![0; 16],
+    };
+
+    let candidates = [invalid, binary];
+
+    let program = glium::Program::new(&display,
+        glium::program::ProgramCreationInput::Binaries {
+            data: &candidates,
+            reflect_builtins: false,
+        }).unwrap();
+    assert!(program.get_attribute("position").is_some());
+
+    match glium::Program::new(&display,
+        glium::program::ProgramCreationInput::Binaries {
+            data: &candidates[.. 1],
+            reflect_builtins: false,
+        })
+    {
+        Err(glium::LinkingError(_)) => (),
+        _ => panic!()
+    };
+
+    display.assert_no_error();
+}
+
+#[test]
+fn cpu_memory_estimate() {
+    let display = support::build_display();
+
+    let vertex_shader = "
+        #version 110
+
+        uniform vec2 offset;
+
+        void main() {
+            gl_Position = vec4(offset, 0.0, 1.0);
+        }
+    ";
+
+    let fragment_shader = "
+        #version 110
+
+        void main() {
+            gl_FragColor = vec4(1.0, 1.0, 1.0, 1.0);
+        }
+    ";
+
+    let program = glium::Program::from_source(&display, vertex_shader, fragment_shader,
+                                              None).unwrap();
+
+    // the source code is retained
+    assert!(program.cpu_memory_estimate() >= vertex_shader.len() + fragment_shader.len());
+
+    display.assert_no_error();
+}
+
+#[test]
+fn missing_version() {
+    let display = support::build_display();
+
+    let source = |missing_version| glium::program::ProgramCreationInput::SourceCode {
+        tessellation_control_shader: None,
+        tessellation_evaluation_shader: None,
+        geometry_shader: None,
+
+        vertex_shader: "
+            void main() {
+                gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        fragment_shader: "
+            #version 110
+
+            void main() {
+                gl_FragColor = vec4(1.0, 1.0, 1.0, 1.0);
+            }
+        ",
+
+        transform_feedback_varyings: None,
+        indexed_frag_data_locations: None,
+        source_transform: None,
+        retain_link_log: false,
+        retain_source: false,
+        strict: false,
+        missing_version: missing_version,
+        reflect_builtins: false,
+    };
+
+    match glium::Program::new(&display, source(glium::program::MissingVersion::Error)) {
+        Err(glium::CompilationError(e)) => {
+            assert_eq!(e.stage, Some(glium::program::ShaderStage::Vertex));
+        },
+        _ => panic!()
+    };
+
+    glium::Program::new(&display, source(glium::program::MissingVersion::Default("110")))
+                   .unwrap();
+
+    display.assert_no_error();
+}
+
+#[test]
+fn binary_is_send() {
+    // binaries are the way to transfer programs between threads
+    fn is_send<T: Send>() {}
+    is_send::<glium::program::Binary>();
+}
+
+#[test]
+fn get_uniform_fields() {
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            uniform vec2 offset;
+            uniform float values[3];
+
+            void main() {
+                gl_Position = vec4(offset, values[0] + values[1] + values[2], 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            void main() {
+                gl_FragColor = vec4(1.0, 1.0, 1.0, 1.0);
+            }
+        ",
+        None).unwrap();
+
+    let offset = program.get_uniform("offset").unwrap();
+    assert!(offset.location >= 0);
+    assert_eq!(offset.ty, glium::uniforms::UniformType::FloatVec2);
+    assert_eq!(offset.size, None);
+
+    // some drivers report the name of arrays as `values[0]`
+    let values = program.get_uniform("values").or(program.get_uniform("values[0]")).unwrap();
+    assert_eq!(values.ty, glium::uniforms::UniformType::Float);
+    assert_eq!(values.size, Some(3));
+
+    display.assert_no_error();
+}
+
+#[test]
+#[cfg(feature = "debug_live_programs")]
+fn live_programs() {
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            void main() {
+                gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            void main() {
+                gl_FragColor = vec4(1.0, 1.0, 1.0, 1.0);
+            }
+        ",
+        None).unwrap();
+
+    let id = glium::GlObject::get_id(&program);
+    assert!(display.live_programs().contains(&id));
+
+    drop(program);
+    assert!(!display.live_programs().contains(&id));
+
+    display.assert_no_error();
+}
+
+#[test]
+fn clone_binary_to() {
+    let display = support::build_display();
+    let display2 = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            uniform vec2 offset;
+
+            void main() {
+                gl_Position = vec4(offset, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            void main() {
+                gl_FragColor = vec4(1.0, 1.0, 1.0, 1.0);
+            }
+        ",
+        None).unwrap();
+
+    let clone = match program.clone_binary_to(&display2) {
+        Some(p) => p,
+        None => return
+    };
+
+    assert!(clone.get_uniform("offset").is_some());
+
+    display.assert_no_error();
+    display2.assert_no_error();
+}
+
+#[test]
+fn transform_feedback_varyings_interleaved_offsets() {
+    let display = support::build_display();
+
+    let source = glium::program::ProgramCreationInput::SourceCode {
+        tessellation_control_shader: None,
+        tessellation_evaluation_shader: None,
+        geometry_shader: None,
+
+        vertex_shader: "
+            #version 130
+
+            out vec2 normal;
+            out float values[2];
+
+            void main() {
+                normal = vec2(0.0, 0.0);
+                values[0] = 1.0;
+                values[1] = 2.0;
+
+                gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        fragment_shader: "
+            #version 130
+
+            out vec4 color;
+
+            void main() {
+                color = vec4(1.0, 1.0, 1.0, 1.0);
+            }
+        ",
+
+        transform_feedback_varyings: Some((
+            vec!["normal".to_string(), "values".to_string()],
+            glium::program::TransformFeedbackMode::Interleaved
+        )),
+
+        indexed_frag_data_locations: None,
+        source_transform: None,
+        retain_link_log: false,
+        retain_source: false,
+        strict: false,
+        missing_version: glium::program::MissingVersion::Ignore,
+        reflect_builtins: false,
+    };
+
+    let program = match glium::Program::new(&display, source) {
+        Ok(p) => p,
+        Err(glium::program::ProgramCreationError::TransformFeedbackNotSupported) => return,
+        Err(e) => panic!("{:?}", e)
+    };
+
+    let varyings = program.get_transform_feedback_varyings();
+    assert_eq!(varyings[0].offset, 0);
+    assert_eq!(varyings[0].size, 2 * 4);
+    assert_eq!(varyings[1].offset, 2 * 4);
+    assert_eq!(varyings[1].size, 2 * 4);
+    assert_eq!(varyings[1].num_components, 2);
+
+    let values = program.get_transform_feedback_varying("values").unwrap();
+    assert_eq!(values.offset, 2 * 4);
+    assert_eq!(program.get_transform_feedback_varying("values[0]").unwrap().offset, 2 * 4);
+    assert!(program.get_transform_feedback_varying("color").is_none());
+
+    display.assert_no_error();
+}
+
+#[test]
+fn supports_feature() {
+    use glium::program::ProgramFeature;
+
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            void main() {
+                gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            void main() {
+                gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        None).unwrap();
+
+    assert_eq!(program.supports_feature(ProgramFeature::BinaryRetrieval),
+               program.get_binary_if_supported().is_some());
+    assert_eq!(program.supports_feature(ProgramFeature::SpirV),
+               program.creation_context_info().spirv);
+
+    display.assert_no_error();
+}
+
+#[test]
+fn compilation_errors_of_all_stages() {
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            void main() {
+                gl_Position = vec4(0.0, 0.0, 0.0, 1.0)
+            }
+        ",
+        "
+            #version 110
+
+            void main() {
+                gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0)
+            }
+        ",
+        None);
+
+    match program {
+        Err(glium::CompilationError(e)) => {
+            assert_eq!(e.stage, Some(glium::program::ShaderStage::Vertex));
+            assert!(e.contains("vertex shader"));
+            assert!(e.contains("fragment shader"));
+        },
+        _ => panic!()
+    };
+
+    display.assert_no_error();
+}
+
+#[test]
+fn forces_early_fragment_tests() {
+    let display = support::build_display();
+
+    let vertex_shader = "
+        #version 110
+
+        void main() {
+            gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+        }
+    ";
+
+    let program = glium::Program::from_source(&display, vertex_shader,
+        "
+            #version 110
+
+            void main() {
+                gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        None).unwrap();
+
+    assert!(!program.forces_early_fragment_tests());
+
+    let program = match glium::Program::from_source(&display, vertex_shader,
+        "
+            #version 420
+
+            layout(early_fragment_tests) in;
+
+            out vec4 color;
+
+            void main() {
+                color = vec4(1.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        None)
+    {
+        Ok(p) => p,
+        Err(_) => return        // GLSL 4.20 may not be supported
+    };
+
+    assert!(program.forces_early_fragment_tests());
+
+    display.assert_no_error();
+}
+
+#[test]
+fn check_attribute() {
+    use glium::vertex::AttributeType;
+
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            attribute vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            void main() {
+                gl_FragColor = vec4(1.0, 1.0, 1.0, 1.0);
+            }
+        ",
+        None).unwrap();
+
+    assert!(program.check_attribute("position", AttributeType::F32F32).is_ok());
+
+    let err = program.check_attribute("position", AttributeType::F32F32F32).unwrap_err();
+    assert_eq!(err.actual, Some(AttributeType::F32F32));
+    assert_eq!(err.expected, AttributeType::F32F32F32);
+
+    let err = program.check_attribute("normal", AttributeType::F32F32F32).unwrap_err();
+    assert_eq!(err.actual, None);
+
+    display.assert_no_error();
+}
+
+#[test]
+fn from_binary_or_source() {
+    let display = support::build_display();
+
+    let source = || glium::program::SourceCode {
+        vertex_shader: "
+            #version 110
+
+            uniform vec2 offset;
+
+            void main() {
+                gl_Position = vec4(offset, 0.0, 1.0);
+            }
+        ",
+        tessellation_control_shader: None,
+        tessellation_evaluation_shader: None,
+        geometry_shader: None,
+        fragment_shader: "
+            #version 110
+
+            void main() {
+                gl_FragColor = vec4(1.0, 1.0, 1.0, 1.0);
+            }
+        ",
+    };
+
+    let (_, used_binary) = glium::Program::from_binary_or_source(&display, None, source())
+                                                                 .unwrap();
+    assert!(!used_binary);
+
+    let garbage = glium::program::Binary { format: 0, content: vec![0, 1, 2, 3] };
+    let (program, used_binary) = glium::Program::from_binary_or_source(&display, Some(garbage),
+                                                                       source()).unwrap();
+    assert!(!used_binary);
+    assert!(program.get_uniform("offset").is_some());
+
+    let binary = match program.get_binary_if_supported() {
+        Some(b) => b,
+        None => return
+    };
+
+    let (program, used_binary) = glium::Program::from_binary_or_source(&display, Some(binary),
+                                                                       source()).unwrap();
+    assert!(used_binary);
+    assert!(program.get_uniform("offset").is_some());
+
+    display.assert_no_error();
+}
+
+#[test]
+fn samplers() {
+    use glium::program::SamplerKind;
+
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 130
+
+            void main() {
+                gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 130
+
+            uniform sampler2D color;
+            uniform sampler2DShadow shadow;
+            uniform float factor;
+
+            out vec4 frag_color;
+
+            void main() {
+                float lit = texture(shadow, vec3(0.5, 0.5, 0.5));
+                frag_color = texture(color, vec2(0.5, 0.5)) * lit * factor;
+            }
+        ",
+        None).unwrap();
+
+    let mut samplers = program.samplers().collect::<Vec<_>>();
+    samplers.sort_by(|a, b| a.0.cmp(b.0));
+
+    assert_eq!(samplers.len(), 2);
+    assert_eq!(samplers[0].0, "color");
+    assert_eq!(samplers[0].1, SamplerKind::Texture2d);
+    assert_eq!(samplers[0].2, program.get_uniform("color").unwrap().location);
+    assert_eq!(samplers[1].0, "shadow");
+    assert_eq!(samplers[1].1, SamplerKind::Shadow2d);
+    assert!(samplers[1].1.is_shadow());
+
+    display.assert_no_error();
+}
+
+#[test]
+fn samplers_cubemap_array() {
+    use glium::program::SamplerKind;
+
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 400
+
+            void main() {
+                gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 400
+
+            uniform samplerCubeArray cubes;
+            uniform samplerCubeArrayShadow shadows;
+            uniform usampler1DArray lines;
+
+            out vec4 frag_color;
+
+            void main() {
+                float lit = texture(shadows, vec4(0.5, 0.5, 0.5, 0.0), 0.5);
+                float line = float(texture(lines, vec2(0.5, 0.0)).r);
+                frag_color = texture(cubes, vec4(0.5, 0.5, 0.5, 0.0)) * lit * line;
+            }
+        ",
+        None);
+
+    // ignoring test in case of compilation error (version may not be supported)
+    let program = match program {
+        Ok(p) => p,
+        Err(_) => return
+    };
+
+    let mut samplers = program.samplers().collect::<Vec<_>>();
+    samplers.sort_by(|a, b| a.0.cmp(b.0));
+
+    assert_eq!(samplers.len(), 3);
+    assert_eq!(samplers[0].0, "cubes");
+    assert_eq!(samplers[0].1, SamplerKind::CubemapArray);
+    assert_eq!(samplers[1].0, "lines");
+    assert_eq!(samplers[1].1, SamplerKind::Texture1dArray);
+    assert_eq!(samplers[2].0, "shadows");
+    assert_eq!(samplers[2].1, SamplerKind::ShadowCubemapArray);
+
+    display.assert_no_error();
+}
+
+#[test]
+fn warm_up() {
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            attribute vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            void main() {
+                gl_FragColor = vec4(1.0, 1.0, 1.0, 1.0);
+            }
+        ",
+        None).unwrap();
+
+    program.warm_up(&display);
+    display.assert_no_error();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 330
+
+            void main() {
+                gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 330
+
+            out vec4 color;
+
+            void main() {
+                color = vec4(1.0, 1.0, 1.0, 1.0);
+            }
+        ",
+        Some("
+            #version 330
+
+            layout(triangles) in;
+            layout(triangle_strip, max_vertices = 3) out;
+
+            void main() {
+                for (int i = 0; i < 3; i++) {
+                    gl_Position = gl_in[i].gl_Position;
+                    EmitVertex();
+                }
+            }
+        "));
+
+    let program = match program {
+        Ok(p) => p,
+        Err(_) => return
+    };
+
+    program.warm_up(&display);
+    display.assert_no_error();
+}
+
+#[test]
+fn writes_point_size() {
+    let display = support::build_display();
+
+    let fragment_shader = "
+        #version 110
+
+        void main() {
+            gl_FragColor = vec4(1.0, 1.0, 1.0, 1.0);
+        }
+    ";
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            void main() {
+                gl_PointSize = 4.0;
+                gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        fragment_shader, None).unwrap();
+
+    assert!(program.writes_point_size());
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            void main() {
+                gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        fragment_shader, None).unwrap();
+
+    assert!(!program.writes_point_size());
+
+    display.assert_no_error();
+}
+
+#[test]
+fn validate() {
+    use glium::program::ValidationError;
+
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            void main() {
+                gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            void main() {
+                gl_FragColor = vec4(1.0, 1.0, 1.0, 1.0);
+            }
+        ",
+        None).unwrap();
+
+    // the result depends on the current state, so we can only check its consistency
+    match program.validate() {
+        Ok(()) | Err(ValidationError::Unsupported) => (),
+        Err(ValidationError::Invalid { status, .. }) => assert_eq!(status, 0),
+    }
+
+    display.assert_no_error();
+}
+
+#[test]
+fn byte_order_mark() {
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "\u{feff}#version 110
+
+        void main() {
+            gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+        }
+        ",
+        "\u{feff}#version 110
+
+        void main() {
+            gl_FragColor = vec4(1.0, 1.0, 1.0, 1.0);
+        }
+        ",
+        None);
+
+    assert!(program.is_ok());
+
+    display.assert_no_error();
+}
+
+#[test]
+fn reflection_visitor() {
+    struct Visitor {
+        uniforms: Vec<String>,
+        attributes: Vec<String>,
+    }
+
+    impl glium::program::ProgramReflectionVisitor for Visitor {
+        fn visit_uniform(&mut self, name: &str, _: &glium::program::Uniform) {
+            self.uniforms.push(name.to_string());
+        }
+
+        fn visit_attribute(&mut self, name: &str, _: &glium::program::Attribute) {
+            self.attributes.push(name.to_string());
+        }
+    }
+
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            uniform vec2 offset;
+            attribute vec2 position;
+
+            void main() {
+                gl_Position = vec4(position + offset, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            uniform vec4 color;
+
+            void main() {
+                gl_FragColor = color;
+            }
+        ",
+        None).unwrap();
+
+    let mut visitor = Visitor { uniforms: Vec::new(), attributes: Vec::new() };
+    program.accept_reflection_visitor(&mut visitor);
+
+    visitor.uniforms.sort();
+    assert_eq!(visitor.uniforms, vec!["color".to_string(), "offset".to_string()]);
+    assert_eq!(visitor.attributes, vec!["position".to_string()]);
+
+    display.assert_no_error();
+}
+
+#[test]
+fn default_precision_on_gles() {
+    let display = support::build_display();
+
+    if display.get_opengl_version().0 != glium::Api::GlEs {
+        return;
+    }
+
+    // no precision qualifier in the fragment shader
+    let program = glium::Program::from_source(&display,
+        "
+            #version 100
+
+            void main() {
+                gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 100
+
+            void main() {
+                float value = 0.5;
+                gl_FragColor = vec4(value, value, value, 1.0);
+            }
+        ",
+        None);
+
+    assert!(program.is_ok());
+
+    display.assert_no_error();
+}
+
+#[test]
+fn get_uniform_default() {
+    let display = support::build_display();
+
+    // uniform initializers require GLSL 1.20
+    let program = match glium::Program::from_source(&display,
+        "
+            #version 120
+
+            uniform vec2 offset = vec2(0.25, 0.5);
+
+            void main() {
+                gl_Position = vec4(offset, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 120
+
+            uniform float value = 2.0;
+            uniform int count;
+
+            void main() {
+                gl_FragColor = vec4(value, float(count), 0.0, 1.0);
+            }
+        ",
+        None)
+    {
+        Ok(p) => p,
+        Err(_) => return
+    };
+
+    match program.get_uniform_default("value") {
+        Some(glium::uniforms::UniformValue::Float(v)) => assert_eq!(v, 2.0),
+        _ => panic!()
+    };
+
+    match program.get_uniform_default("offset") {
+        Some(glium::uniforms::UniformValue::Vec2(v)) => assert_eq!(v, [0.25, 0.5]),
+        _ => panic!()
+    };
+
+    match program.get_uniform_default("count") {
+        Some(glium::uniforms::UniformValue::SignedInt(v)) => assert_eq!(v, 0),
+        _ => panic!()
+    };
+
+    assert!(program.get_uniform_default("foo").is_none());
+
+    // modifying the value doesn't change the default
+    let mut program = program;
+    program.set_uniform("value", 5.0f32).unwrap();
+    program.refresh_reflection().unwrap();
+
+    match program.get_uniform_default("value") {
+        Some(glium::uniforms::UniformValue::Float(v)) => assert_eq!(v, 2.0),
+        _ => panic!()
+    };
+
+    display.assert_no_error();
+}
+
+#[test]
+fn invalidate_vaos() {
+    let display = support::build_display();
+    let (vb, ib, program) = support::build_fullscreen_red_pipeline(&display);
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vb, &ib, &program, &glium::uniforms::EmptyUniforms,
+                              &Default::default()).unwrap();
+
+    // the VAO is rebuilt on the next draw
+    program.invalidate_vaos();
+
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vb, &ib, &program, &glium::uniforms::EmptyUniforms,
+                              &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
+
+    display.assert_no_error();
+}
+
+#[test]
+fn required_extension_not_supported() {
+    let display = support::build_display();
+
+    let vertex_shader = "
+        #version 110
+        #extension GL_GLIUM_nonexisting_extension : require
+
+        void main() {
+            gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+        }
+    ";
+
+    let fragment_shader = "
+        #version 110
+
+        void main() {
+            gl_FragColor = vec4(1.0, 1.0, 1.0, 1.0);
+        }
+    ";
+
+    match glium::Program::from_source(&display, vertex_shader, fragment_shader, None) {
+        Err(glium::program::ProgramCreationError::ExtensionNotSupported(stage, ext)) => {
+            assert_eq!(stage, glium::program::ShaderStage::Vertex);
+            assert_eq!(ext, "GL_GLIUM_nonexisting_extension");
+        },
+        _ => panic!()
+    };
+
+    display.assert_no_error();
+}
+
+#[test]
+fn enabled_extension_not_checked() {
+    let display = support::build_display();
+
+    // `enable` only produces a warning if the extension is not supported
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+            #extension GL_GLIUM_nonexisting_extension : enable
+
+            void main() {
+                gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+            #extension GL_GLIUM_nonexisting_extension : warn
+
+            void main() {
+                gl_FragColor = vec4(1.0, 1.0, 1.0, 1.0);
+            }
+        ",
+        None);
+
+    match program {
+        Err(glium::program::ProgramCreationError::ExtensionNotSupported(_, _)) => panic!(),
+        _ => ()
+    };
+
+    display.assert_no_error();
+}
+
+#[test]
+fn double_attribute() {
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 410
+
+            in dvec3 position;
+
+            void main() {
+                gl_Position = vec4(vec3(position), 1.0);
+            }
+        ",
+        "
+            #version 410
+
+            out vec4 color;
+
+            void main() {
+                color = vec4(1.0, 1.0, 1.0, 1.0);
+            }
+        ",
+        None);
+
+    // ignoring test in case of compilation error (version may not be supported)
+    let program = match program {
+        Ok(p) => p,
+        Err(_) => return
+    };
+
+    assert_eq!(program.get_attribute("position").unwrap().ty,
+               glium::vertex::AttributeType::F64F64F64);
+
+    display.assert_no_error();
+}
@@ -173,3 +173,88 @@ fn uniform_wrong_type() {
 
     display.assert_no_error();
 }
+
+#[test]
+fn program_set_uniform() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            attribute vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            uniform vec4 color;
+
+            void main() {
+                gl_FragColor = color;
+            }
+        ",
+        None).unwrap();
+
+    program.set_uniform("color", [1.0, 0.0, 0.0, 0.5f32]).unwrap();
+    assert!(program.set_uniform("color", 1.0f32).is_err());
+
+    match program.set_uniform("unexisting", 1.0f32) {
+        Err(err) => assert_eq!(err.expected, None),
+        Ok(_) => panic!()
+    };
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vb, &ib, &program, &glium::uniforms::EmptyUniforms,
+                              &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (255, 0, 0));
+    assert_eq!(data.last().unwrap().last().unwrap(), &(255, 0, 0));
+
+    display.assert_no_error();
+}
+
+#[test]
+fn program_set_uniform_checked() {
+    use glium::uniforms::UniformType;
+
+    let display = support::build_display();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            attribute vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            uniform vec4 color;
+
+            void main() {
+                gl_FragColor = color;
+            }
+        ",
+        None).unwrap();
+
+    program.set_uniform_checked("color", [1.0, 0.0, 0.0, 0.5f32]).unwrap();
+
+    let err = program.set_uniform_checked("color", [1.0, 0.0, 0.0f32]).unwrap_err();
+    assert_eq!(err.expected, Some(UniformType::FloatVec4));
+    assert_eq!(err.obtained, Some(UniformType::FloatVec3));
+
+    let err = program.set_uniform_checked("unexisting", 1.0f32).unwrap_err();
+    assert_eq!(err.expected, None);
+
+    display.assert_no_error();
+}